@@ -11,8 +11,28 @@ pub struct Scanner {
     start: usize,
     current: usize,
     line: u32,
+    had_error: bool,
+    /// Number of `{` currently open, including those starting an interpolation.
+    braces: usize,
+    /// The strings suspended at each enclosing `${`, innermost last.
+    interpolations: Vec<Interpolation>,
+    /// The imported module being scanned, named in error messages.
+    module: Option<String>,
 }
 
+/// A string whose scanning is suspended inside one of its `${...}`.
+struct Interpolation {
+    /// Brace depth at which the `${` was opened.
+    braces: usize,
+    /// The pieces scanned so far of a triple-quoted string, which can only be
+    /// dedented once all of it has been seen.
+    triple: Option<Vec<Piece>>,
+}
+
+/// Part of a triple-quoted string: the index of its token, its raw text and
+/// the line it starts on.
+type Piece = (usize, String, u32);
+
 impl Scanner {
     pub fn new(source: &str) -> Scanner {
         Scanner {
@@ -21,6 +41,30 @@ impl Scanner {
             start: 0,
             current: 0,
             line: 1,
+            had_error: false,
+            braces: 0,
+            interpolations: Vec::new(),
+            module: None,
+        }
+    }
+
+    pub fn set_module(&mut self, name: &str) {
+        self.module = Some(name.to_string());
+    }
+
+    pub fn had_error(&self) -> bool {
+        self.had_error
+    }
+
+    fn error(&mut self, message: &str) {
+        self.error_at(self.line, message);
+    }
+
+    fn error_at(&mut self, line: u32, message: &str) {
+        self.had_error = true;
+        match &self.module {
+            Some(module) => error(line, &format!("In module \"{}\": {}", module, message)),
+            None => error(line, message),
         }
     }
 
@@ -29,6 +73,9 @@ impl Scanner {
             self.start = self.current;
             self.scan_token();
         }
+        if !self.interpolations.is_empty() {
+            self.error("Unterminated string interpolation.");
+        }
 
         self.tokens.push(token::Token::new(
             token_type::TokenType::EOF,
@@ -48,14 +95,87 @@ impl Scanner {
         match c {
             '(' => self.add_token(TokenType::LEFT_PAREN, None),
             ')' => self.add_token(TokenType::RIGHT_PAREN, None),
-            '{' => self.add_token(TokenType::LEFT_BRACE, None),
-            '}' => self.add_token(TokenType::RIGHT_BRACE, None),
+            '{' => {
+                self.braces += 1;
+                self.add_token(TokenType::LEFT_BRACE, None);
+            }
+            '}' if self
+                .interpolations
+                .last()
+                .is_some_and(|interpolation| interpolation.braces == self.braces) =>
+            {
+                let interpolation = self.interpolations.pop().unwrap();
+                self.braces -= 1;
+                self.scan_string_body(false, interpolation.triple);
+            }
+            '}' => {
+                self.braces = self.braces.saturating_sub(1);
+                self.add_token(TokenType::RIGHT_BRACE, None);
+            }
+            '[' => self.add_token(TokenType::LEFT_BRACKET, None),
+            ']' => self.add_token(TokenType::RIGHT_BRACKET, None),
+            ':' => self.add_token(TokenType::COLON, None),
+            '?' => {
+                let token_type = if self.is_next_char('?') {
+                    TokenType::QUESTION_QUESTION
+                } else {
+                    TokenType::QUESTION
+                };
+                self.add_token(token_type, None);
+            }
             ',' => self.add_token(TokenType::COMMA, None),
             '.' => self.add_token(TokenType::DOT, None),
-            '-' => self.add_token(TokenType::MINUS, None),
-            '+' => self.add_token(TokenType::PLUS, None),
+            '-' => {
+                let token_type = if self.is_next_char('-') {
+                    TokenType::MINUS_MINUS
+                } else if self.is_next_char('=') {
+                    TokenType::MINUS_EQUAL
+                } else {
+                    TokenType::MINUS
+                };
+                self.add_token(token_type, None);
+            }
+            '+' => {
+                let token_type = if self.is_next_char('+') {
+                    TokenType::PLUS_PLUS
+                } else if self.is_next_char('=') {
+                    TokenType::PLUS_EQUAL
+                } else {
+                    TokenType::PLUS
+                };
+                self.add_token(token_type, None);
+            }
             ';' => self.add_token(TokenType::SEMICOLON, None),
-            '*' => self.add_token(TokenType::STAR, None),
+            '*' => {
+                let token_type = if self.is_next_char('*') {
+                    TokenType::STAR_STAR
+                } else if self.is_next_char('=') {
+                    TokenType::STAR_EQUAL
+                } else {
+                    TokenType::STAR
+                };
+                self.add_token(token_type, None);
+            }
+            '%' => {
+                let token_type = if self.is_next_char('=') {
+                    TokenType::PERCENT_EQUAL
+                } else {
+                    TokenType::PERCENT
+                };
+                self.add_token(token_type, None);
+            }
+            // Integer division can't be spelled `//`, which starts a comment.
+            '~' => {
+                let token_type = if self.is_next_char('/') {
+                    TokenType::TILDE_SLASH
+                } else {
+                    TokenType::TILDE
+                };
+                self.add_token(token_type, None);
+            }
+            '&' => self.add_token(TokenType::AMPERSAND, None),
+            '|' => self.add_token(TokenType::PIPE, None),
+            '^' => self.add_token(TokenType::CARET, None),
             '!' => {
                 let token_type = if self.is_next_char('=') {
                     TokenType::BANG_EQUAL
@@ -67,6 +187,8 @@ impl Scanner {
             '=' => {
                 let token_type = if self.is_next_char('=') {
                     TokenType::EQUAL_EQUAL
+                } else if self.is_next_char('>') {
+                    TokenType::FAT_ARROW
                 } else {
                     TokenType::EQUAL
                 };
@@ -75,6 +197,8 @@ impl Scanner {
             '<' => {
                 let token_type = if self.is_next_char('=') {
                     TokenType::LESS_EQUAL
+                } else if self.is_next_char('<') {
+                    TokenType::LESS_LESS
                 } else {
                     TokenType::LESS
                 };
@@ -83,6 +207,8 @@ impl Scanner {
             '>' => {
                 let token_type = if self.is_next_char('=') {
                     TokenType::GREATER_EQUAL
+                } else if self.is_next_char('>') {
+                    TokenType::GREATER_GREATER
                 } else {
                     TokenType::GREATER
                 };
@@ -93,23 +219,50 @@ impl Scanner {
                     while !self.is_at_end() && self.peek() != '\n' {
                         self.advance();
                     }
+                } else if self.is_next_char('*') {
+                    self.block_comment();
+                } else if self.is_next_char('=') {
+                    self.add_token(TokenType::SLASH_EQUAL, None);
                 } else {
                     self.add_token(TokenType::SLASH, None);
                 }
             }
             ' ' | '\t' | '\r' => {}
             '\n' => self.line += 1,
-            '"' => self.handle_string(),
+            '"' => self.handle_string(false),
+            'r' if self.peek() == '"' => {
+                self.advance();
+                self.handle_string(true);
+            }
             '0'..='9' => self.handle_number(),
-            'a'..='z' | 'A'..='Z' => self.handle_identifier(),
+            c if c == '_' || unicode_ident::is_xid_start(c) => self.handle_identifier(),
             _ => {
-                error(self.line, "Unexpected character.");
+                self.error("Unexpected character.");
+            }
+        }
+    }
+
+    /// Skips a `/* ... */` comment whose opening has been consumed. Block
+    /// comments nest, so each `/*` needs its own `*/`.
+    fn block_comment(&mut self) {
+        let start_line = self.line;
+        let mut depth = 1;
+        while depth > 0 {
+            if self.is_at_end() {
+                self.error_at(start_line, "Unterminated block comment.");
+                return;
+            }
+            match self.advance() {
+                '/' if self.is_next_char('*') => depth += 1,
+                '*' if self.is_next_char('/') => depth -= 1,
+                '\n' => self.line += 1,
+                _ => {}
             }
         }
     }
 
     fn handle_identifier(&mut self) {
-        while self.peek().is_alphanumeric() {
+        while unicode_ident::is_xid_continue(self.peek()) {
             self.advance();
         }
         let text = self.source[self.start..self.current]
@@ -126,40 +279,112 @@ impl Scanner {
             .clone()
     }
 
+    /// Scans a number literal whose first digit has been consumed: decimal ints
+    /// and floats with an optional exponent, or `0x`, `0o` and `0b` ints. Digits
+    /// may be separated by single underscores.
     fn handle_number(&mut self) {
-        while self.peek().is_ascii_digit() {
+        let radix = match (self.source[self.start], self.peek()) {
+            ('0', 'x' | 'X') => Some((16, "hex")),
+            ('0', 'o' | 'O') => Some((8, "octal")),
+            ('0', 'b' | 'B') => Some((2, "binary")),
+            _ => None,
+        };
+        if let Some((radix, name)) = radix {
             self.advance();
+            return self.handle_radix_number(radix, name);
         }
 
+        self.consume_digits();
+        let mut is_float = false;
         if self.peek() == '.' && self.peek_next().is_ascii_digit() {
+            is_float = true;
+            self.advance();
+            self.consume_digits();
+        }
+        if matches!(self.peek(), 'e' | 'E') {
+            is_float = true;
             self.advance();
-            while self.peek().is_ascii_digit() {
+            if matches!(self.peek(), '+' | '-') {
                 self.advance();
             }
-            self.add_token(
-                token_type::TokenType::NUMBER,
-                Some(Literal::Float(
-                    self.source[self.start..self.current]
-                        .iter()
-                        .collect::<String>()
-                        .parse::<f64>()
-                        .unwrap(),
-                )),
-            );
+            if !self.peek().is_ascii_digit() {
+                self.error("Expect digits in exponent.");
+                return;
+            }
+            self.consume_digits();
+        }
+
+        let Some(text) = self.number_text(10) else {
+            return;
+        };
+        if is_float {
+            match text.parse::<f64>() {
+                Ok(value) => {
+                    self.add_token(token_type::TokenType::NUMBER, Some(Literal::Float(value)))
+                }
+                Err(_) => self.error("Invalid number literal."),
+            }
         } else {
-            self.add_token(
-                token_type::TokenType::NUMBER,
-                Some(Literal::Int(
-                    self.source[self.start..self.current]
-                        .iter()
-                        .collect::<String>()
-                        .parse::<i32>()
-                        .unwrap(),
-                )),
-            );
+            match text.parse::<i64>() {
+                Ok(value) => {
+                    self.add_token(token_type::TokenType::NUMBER, Some(Literal::Int(value)))
+                }
+                Err(_) => self.error("Integer literal is too large."),
+            }
         }
     }
 
+    fn handle_radix_number(&mut self, radix: u32, name: &str) {
+        let digits_start = self.current;
+        // Take every alphanumeric so that a stray digit is reported rather than
+        // silently starting the next token.
+        while self.peek().is_ascii_alphanumeric() || self.peek() == '_' {
+            self.advance();
+        }
+        if self.current == digits_start {
+            let prefix: String = self.source[self.start..self.current].iter().collect();
+            self.error(&format!("Expect digits after '{}'.", prefix));
+            return;
+        }
+        if let Some(&digit) = self.source[digits_start..self.current]
+            .iter()
+            .find(|c| **c != '_' && !c.is_digit(radix))
+        {
+            self.error(&format!("Invalid digit '{}' in {} literal.", digit, name));
+            return;
+        }
+        let Some(text) = self.number_text(radix) else {
+            return;
+        };
+        match i64::from_str_radix(&text[2..], radix) {
+            Ok(value) => self.add_token(token_type::TokenType::NUMBER, Some(Literal::Int(value))),
+            Err(_) => self.error("Integer literal is too large."),
+        }
+    }
+
+    fn consume_digits(&mut self) {
+        while self.peek().is_ascii_digit() || self.peek() == '_' {
+            self.advance();
+        }
+    }
+
+    /// Returns the scanned literal without its underscores, or reports an error
+    /// if an underscore isn't between two digits.
+    fn number_text(&mut self, radix: u32) -> Option<String> {
+        let lexeme = &self.source[self.start..self.current];
+        let misplaced = lexeme.iter().enumerate().any(|(i, c)| {
+            *c == '_'
+                && !(i > 0
+                    && lexeme[i - 1].is_digit(radix)
+                    && lexeme.get(i + 1).is_some_and(|c| c.is_digit(radix)))
+        });
+        if misplaced {
+            self.error("Misplaced '_' in number literal.");
+            return None;
+        }
+        Some(lexeme.iter().filter(|c| **c != '_').collect())
+    }
+
     fn peek_next(&self) -> char {
         *self
             .source
@@ -168,26 +393,167 @@ impl Scanner {
             .unwrap()
     }
 
-    fn handle_string(&mut self) {
-        while !self.is_at_end() && self.peek() != '"' {
-            if self.peek() == '\n' {
-                self.line += 1;
+    fn peek_at(&self, offset: usize) -> char {
+        *self.source.get(self.current + offset).unwrap_or(&'\0')
+    }
+
+    /// Scans a string literal after its opening quote. Triple-quoted strings may
+    /// span lines and have their common indentation stripped; raw strings skip
+    /// escape processing.
+    fn handle_string(&mut self, raw: bool) {
+        let triple = self.peek() == '"' && self.peek_next() == '"';
+        if triple {
+            self.advance();
+            self.advance();
+        }
+        self.scan_string_body(raw, triple.then(Vec::new));
+    }
+
+    /// Scans string contents up to the closing quote, or up to a `${` in a
+    /// non-raw string. In the latter case an INTERPOLATION token is added for
+    /// the text so far and scanning resumes here once the matching `}` is
+    /// reached. `triple` holds the earlier pieces of a triple-quoted string.
+    fn scan_string_body(&mut self, raw: bool, triple: Option<Vec<Piece>>) {
+        let start_line = self.line;
+        let content_start = self.current;
+
+        loop {
+            if self.is_at_end() {
+                self.error("Unterminated string.");
+                return;
+            }
+            match self.peek() {
+                '"' if triple.is_none() => break,
+                '"' if self.peek_next() == '"' && self.peek_at(2) == '"' => break,
+                '$' if !raw && self.peek_next() == '{' => break,
+                '\\' if !raw => {
+                    self.advance();
+                    if self.peek() == '\n' {
+                        self.line += 1;
+                    }
+                }
+                '\n' => self.line += 1,
+                _ => {}
             }
+            if !self.is_at_end() {
+                self.advance();
+            }
+        }
+
+        let text: String = self.source[content_start..self.current].iter().collect();
+        if self.peek() == '$' {
             self.advance();
+            self.advance();
+            self.braces += 1;
+            let triple = match triple {
+                Some(mut pieces) => {
+                    // Filled in once the whole string has been scanned.
+                    pieces.push((self.tokens.len(), text, start_line));
+                    self.add_token(
+                        TokenType::INTERPOLATION,
+                        Some(Literal::String(String::new())),
+                    );
+                    Some(pieces)
+                }
+                None => {
+                    let text = self.unescape(&text, start_line);
+                    self.add_token(TokenType::INTERPOLATION, Some(Literal::String(text)));
+                    None
+                }
+            };
+            self.interpolations.push(Interpolation {
+                braces: self.braces,
+                triple,
+            });
+            return;
         }
-        if self.is_at_end() {
-            error(self.line, "Unterminated string.");
+
+        let Some(mut pieces) = triple else {
+            self.advance();
+            let text = if raw {
+                text
+            } else {
+                self.unescape(&text, start_line)
+            };
+            self.add_token(token_type::TokenType::STRING, Some(Literal::String(text)));
             return;
+        };
+        for _ in 0..3 {
+            self.advance();
         }
-        self.advance();
-        self.add_token(
-            token_type::TokenType::STRING,
-            Some(Literal::String(
-                self.source[self.start + 1..self.current - 1]
-                    .iter()
-                    .collect(),
-            )),
+        pieces.push((self.tokens.len(), text, start_line));
+        let texts = dedent(
+            &pieces
+                .iter()
+                .map(|(_, text, _)| text.as_str())
+                .collect::<Vec<&str>>(),
         );
+        let mut literals: Vec<Literal> = pieces
+            .iter()
+            .zip(texts)
+            .map(|((_, _, line), text)| {
+                Literal::String(if raw {
+                    text
+                } else {
+                    self.unescape(&text, *line)
+                })
+            })
+            .collect();
+        let last = literals.pop();
+        for ((index, _, _), literal) in pieces.iter().zip(literals) {
+            self.tokens[*index].literal = Some(literal);
+        }
+        self.add_token(token_type::TokenType::STRING, last);
+    }
+
+    /// Replaces escape sequences in `text`, reporting unknown ones against the
+    /// line they appear on.
+    fn unescape(&mut self, text: &str, start_line: u32) -> String {
+        let mut result = String::new();
+        let mut line = start_line;
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c == '\n' {
+                line += 1;
+            }
+            if c != '\\' {
+                result.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('n') => result.push('\n'),
+                Some('t') => result.push('\t'),
+                Some('r') => result.push('\r'),
+                Some('0') => result.push('\0'),
+                Some('\\') => result.push('\\'),
+                Some('"') => result.push('"'),
+                Some('$') => result.push('$'),
+                Some('u') => {
+                    let mut digits = String::new();
+                    let braced = chars.next_if_eq(&'{').is_some();
+                    while let Some(digit) = chars.next_if(|c| c.is_ascii_hexdigit()) {
+                        digits.push(digit);
+                    }
+                    let closed = braced && chars.next_if_eq(&'}').is_some();
+                    let escaped = u32::from_str_radix(&digits, 16)
+                        .ok()
+                        .filter(|_| closed && digits.len() <= 6)
+                        .and_then(char::from_u32);
+                    match escaped {
+                        Some(escaped) => result.push(escaped),
+                        None => self.error_at(line, "Invalid unicode escape sequence."),
+                    }
+                }
+                Some(other) => {
+                    if other == '\n' {
+                        line += 1;
+                    }
+                    self.error_at(line, &format!("Unknown escape sequence '\\{}'.", other));
+                }
+                None => self.error_at(line, "Unterminated escape sequence."),
+            }
+        }
+        result
     }
 
     fn is_next_char(&mut self, expected: char) -> bool {
@@ -227,3 +593,264 @@ impl Scanner {
         ));
     }
 }
+
+/// Normalizes the body of a triple-quoted string, given as the pieces of text
+/// around its interpolations: a newline directly after the opening quotes and
+/// a whitespace-only last line are dropped, then the indentation shared by
+/// every non-blank line is removed. A line holding an interpolation is never
+/// blank.
+fn dedent(pieces: &[&str]) -> Vec<String> {
+    let mut pieces: Vec<Vec<&str>> = pieces.iter().map(|p| p.split('\n').collect()).collect();
+    if let Some(lines) = pieces.first_mut() {
+        if lines.len() > 1 && (lines[0].is_empty() || lines[0] == "\r") {
+            lines.remove(0);
+        }
+    }
+    if let Some(lines) = pieces.last_mut() {
+        if lines.len() > 1 && lines.last().is_some_and(|l| l.trim().is_empty()) {
+            lines.pop();
+        }
+    }
+
+    // Every line but the first of a piece starts a source line, as does the
+    // first line of the whole string. The last line of every piece but the
+    // final one runs on into an interpolation.
+    let last_piece = pieces.len().saturating_sub(1);
+    let starts_line = |piece: usize, line: usize| piece == 0 || line > 0;
+    let is_blank = |piece: usize, line: usize, lines: &[&str]| {
+        lines[line].trim().is_empty() && (piece == last_piece || line + 1 < lines.len())
+    };
+    let indent = pieces
+        .iter()
+        .enumerate()
+        .flat_map(|(p, lines)| {
+            (0..lines.len())
+                .filter(move |&l| starts_line(p, l) && !is_blank(p, l, lines))
+                .map(move |l| lines[l].len() - lines[l].trim_start_matches([' ', '\t']).len())
+        })
+        .min()
+        .unwrap_or(0);
+    pieces
+        .iter()
+        .enumerate()
+        .map(|(p, lines)| {
+            (0..lines.len())
+                .map(|l| {
+                    if !starts_line(p, l) {
+                        lines[l]
+                    } else if is_blank(p, l, lines) {
+                        ""
+                    } else {
+                        &lines[l][indent..]
+                    }
+                })
+                .collect::<Vec<&str>>()
+                .join("\n")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(source: &str) -> (Vec<token::Token>, bool) {
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().clone();
+        (tokens, scanner.had_error())
+    }
+
+    #[test]
+    fn test_integer_literals() {
+        let (tokens, had_error) = scan("3000000000 9223372036854775807");
+        assert!(!had_error);
+        assert_eq!(tokens[0].literal, Some(Literal::Int(3_000_000_000)));
+        assert_eq!(tokens[1].literal, Some(Literal::Int(i64::MAX)));
+    }
+
+    #[test]
+    fn test_integer_literal_too_large() {
+        let (tokens, had_error) = scan("9223372036854775808;");
+        assert!(had_error);
+        assert_eq!(tokens[0].token_type, TokenType::SEMICOLON);
+    }
+
+    fn string_literal(source: &str) -> String {
+        let (tokens, had_error) = scan(source);
+        assert!(!had_error);
+        match &tokens[0].literal {
+            Some(Literal::String(s)) => s.clone(),
+            _ => panic!("expected a string literal"),
+        }
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        assert_eq!(
+            string_literal(r#""a\tb\nc \\ \"q\" \u{48}\u{1F600}""#),
+            "a\tb\nc \\ \"q\" H\u{1F600}"
+        );
+    }
+
+    #[test]
+    fn test_unknown_escape() {
+        let (_, had_error) = scan("\"line one\nbad \\q\"");
+        assert!(had_error);
+        let (_, had_error) = scan(r#""\u{110000}""#);
+        assert!(had_error);
+    }
+
+    #[test]
+    fn test_raw_string() {
+        assert_eq!(string_literal(r#"r"C:\new\dir""#), r"C:\new\dir");
+        let (tokens, _) = scan("r");
+        assert_eq!(tokens[0].token_type, TokenType::IDENTIFIER);
+    }
+
+    #[test]
+    fn test_triple_quoted_string() {
+        let source = "\"\"\"\n    first\n      second\n\n    third\n    \"\"\";";
+        assert_eq!(string_literal(source), "first\n  second\n\nthird");
+        let (tokens, _) = scan(source);
+        assert_eq!(tokens[1].line, 6);
+        assert_eq!(string_literal(r#""""say "hi"\n""""#), "say \"hi\"\n");
+    }
+
+    #[test]
+    fn test_interpolated_triple_quoted_string() {
+        let source = "\"\"\"\n    Dear ${name},\n\n      ${\"\"\"\n        ${x}\n        \"\"\"}!\n    \"\"\"";
+        let (tokens, had_error) = scan(source);
+        assert!(!had_error);
+        let strings: Vec<(TokenType, Option<Literal>)> = tokens
+            .iter()
+            .filter(|t| matches!(t.token_type, TokenType::STRING | TokenType::INTERPOLATION))
+            .map(|t| (t.token_type, t.literal.clone()))
+            .collect();
+        let string = |s: &str| Some(Literal::String(s.to_string()));
+        assert_eq!(
+            strings,
+            vec![
+                (TokenType::INTERPOLATION, string("Dear ")),
+                (TokenType::INTERPOLATION, string(",\n\n  ")),
+                (TokenType::INTERPOLATION, string("")),
+                (TokenType::STRING, string("")),
+                (TokenType::STRING, string("!")),
+            ]
+        );
+        let (tokens, _) = scan("r\"\"\"${x}\"\"\"");
+        assert_eq!(tokens[0].literal, string("${x}"));
+    }
+
+    #[test]
+    fn test_interpolated_string_tokens() {
+        let (tokens, had_error) = scan(r#""a ${ {"k": "${x}"}["k"] } b \${c}""#);
+        assert!(!had_error);
+        let types: Vec<TokenType> = tokens.iter().map(|t| t.token_type).collect();
+        assert_eq!(
+            types,
+            vec![
+                TokenType::INTERPOLATION,
+                TokenType::LEFT_BRACE,
+                TokenType::STRING,
+                TokenType::COLON,
+                TokenType::INTERPOLATION,
+                TokenType::IDENTIFIER,
+                TokenType::STRING,
+                TokenType::RIGHT_BRACE,
+                TokenType::LEFT_BRACKET,
+                TokenType::STRING,
+                TokenType::RIGHT_BRACKET,
+                TokenType::STRING,
+                TokenType::EOF,
+            ]
+        );
+        assert_eq!(
+            tokens[11].literal,
+            Some(Literal::String(" b ${c}".to_string()))
+        );
+        let (_, had_error) = scan("\"a ${b");
+        assert!(had_error);
+    }
+
+    #[test]
+    fn test_number_literal_forms() {
+        let (tokens, had_error) =
+            scan("0xFF 0b1010 0o755 1_000_000 1.5e-3 2E3 0x7fff_ffff_ffff_ffff 1.25");
+        assert!(!had_error);
+        let literals: Vec<Literal> = tokens[..8]
+            .iter()
+            .map(|t| t.literal.clone().unwrap())
+            .collect();
+        assert_eq!(
+            literals,
+            vec![
+                Literal::Int(255),
+                Literal::Int(10),
+                Literal::Int(493),
+                Literal::Int(1_000_000),
+                Literal::Float(1.5e-3),
+                Literal::Float(2000.0),
+                Literal::Int(i64::MAX),
+                Literal::Float(1.25),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_malformed_number_literals() {
+        for source in [
+            "0x",
+            "0b102",
+            "1e",
+            "1e+",
+            "1_",
+            "1__0",
+            "0x_1",
+            "0x1_0000_0000_0000_0000",
+        ] {
+            let (_, had_error) = scan(source);
+            assert!(had_error, "{} should not scan", source);
+        }
+    }
+
+    #[test]
+    fn test_block_comments() {
+        let (tokens, had_error) = scan("1 /* outer /* inner\n */ still\n comment */ 2 /**/ 3");
+        assert!(!had_error);
+        let lines: Vec<(TokenType, u32)> = tokens.iter().map(|t| (t.token_type, t.line)).collect();
+        assert_eq!(
+            lines,
+            vec![
+                (TokenType::NUMBER, 1),
+                (TokenType::NUMBER, 3),
+                (TokenType::NUMBER, 3),
+                (TokenType::EOF, 3),
+            ]
+        );
+        let (_, had_error) = scan("/* /* */");
+        assert!(had_error);
+    }
+
+    #[test]
+    fn test_unicode_and_underscore_identifiers() {
+        let (tokens, had_error) = scan("my_var _private größe 变量 x1 var");
+        assert!(!had_error);
+        let lexemes: Vec<(TokenType, &str)> = tokens[..6]
+            .iter()
+            .map(|t| (t.token_type, t.lexeme.as_str()))
+            .collect();
+        assert_eq!(
+            lexemes,
+            vec![
+                (TokenType::IDENTIFIER, "my_var"),
+                (TokenType::IDENTIFIER, "_private"),
+                (TokenType::IDENTIFIER, "größe"),
+                (TokenType::IDENTIFIER, "变量"),
+                (TokenType::IDENTIFIER, "x1"),
+                (TokenType::VAR, "var"),
+            ]
+        );
+        let (_, had_error) = scan("a → b");
+        assert!(had_error);
+    }
+}
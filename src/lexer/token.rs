@@ -5,7 +5,7 @@ use super::token_type;
 #[derive(Debug, PartialEq, Clone)]
 pub enum Literal {
     String(String),
-    Int(i32),
+    Int(i64),
     Boolean(bool),
     Float(f64),
     Nil,
@@ -8,6 +8,10 @@ pub enum TokenType {
     RIGHT_PAREN,
     LEFT_BRACE,
     RIGHT_BRACE,
+    LEFT_BRACKET,
+    RIGHT_BRACKET,
+    COLON,
+    QUESTION,
     COMMA,
     DOT,
     MINUS,
@@ -15,34 +19,68 @@ pub enum TokenType {
     SEMICOLON,
     SLASH,
     STAR,
+    PERCENT,
+    AMPERSAND,
+    PIPE,
+    CARET,
+    TILDE,
     // One or two character tokens.
+    STAR_STAR,
+    TILDE_SLASH,
+    PLUS_EQUAL,
+    MINUS_EQUAL,
+    STAR_EQUAL,
+    SLASH_EQUAL,
+    PERCENT_EQUAL,
+    PLUS_PLUS,
+    MINUS_MINUS,
+    QUESTION_QUESTION,
     BANG,
     BANG_EQUAL,
     EQUAL,
     EQUAL_EQUAL,
+    FAT_ARROW,
     GREATER,
     GREATER_EQUAL,
     LESS,
     LESS_EQUAL,
+    LESS_LESS,
+    GREATER_GREATER,
     // Literals.
     IDENTIFIER,
     STRING,
+    /// The part of an interpolated string before an embedded `${...}`.
+    INTERPOLATION,
     NUMBER,
     // Keywords.
     AND,
+    AS,
+    BREAK,
+    CATCH,
     CLASS,
+    CONST,
+    CONTINUE,
     ELSE,
+    EXPORT,
     FALSE,
+    FINALLY,
     FUN,
     FOR,
+    FROM,
     IF,
+    IMPORT,
+    IN,
+    LET,
+    MATCH,
     NIL,
     OR,
     PRINT,
     RETURN,
     SUPER,
     THIS,
+    THROW,
     TRUE,
+    TRY,
     VAR,
     WHILE,
     EOF,
@@ -50,19 +88,33 @@ pub enum TokenType {
 
 pub static KEYWORDS: phf::Map<&'static str, TokenType> = phf_map! {
     "and" => TokenType::AND,
+    "as" => TokenType::AS,
+    "break" => TokenType::BREAK,
+    "catch" => TokenType::CATCH,
     "class" => TokenType::CLASS,
+    "const" => TokenType::CONST,
+    "continue" => TokenType::CONTINUE,
     "else" => TokenType::ELSE,
+    "export" => TokenType::EXPORT,
     "false" => TokenType::FALSE,
+    "finally" => TokenType::FINALLY,
     "for" => TokenType::FOR,
+    "from" => TokenType::FROM,
     "fun" => TokenType::FUN,
     "if" => TokenType::IF,
+    "import" => TokenType::IMPORT,
+    "in" => TokenType::IN,
+    "let" => TokenType::LET,
+    "match" => TokenType::MATCH,
     "pls_no" => TokenType::NIL,
     "or" => TokenType::OR,
     "print" => TokenType::PRINT,
     "return" => TokenType::RETURN,
     "super" => TokenType::SUPER,
     "this" => TokenType::THIS,
+    "throw" => TokenType::THROW,
     "true" => TokenType::TRUE,
+    "try" => TokenType::TRY,
     "var" => TokenType::VAR,
     "while" => TokenType::WHILE,
 };
@@ -2,7 +2,9 @@ use std::env;
 use std::fs;
 use std::io;
 use std::io::Write;
+use std::path::Path;
 use std::process::exit;
+use std::thread;
 
 mod lexer;
 mod log;
@@ -10,7 +12,7 @@ mod parser;
 
 use log::log_message::print_code_error;
 use log::log_message::print_error_msg;
-use parser::interpreter::Interpreter;
+use parser::interpreter::{Interpreter, STACK_SIZE};
 
 struct Rox {
     interpreter: Interpreter,
@@ -35,7 +37,12 @@ impl Rox {
 
     fn execute_file(&mut self, file_path: &str) {
         match fs::read_to_string(file_path) {
-            Ok(contents) => self.execute(contents),
+            Ok(contents) => {
+                self.interpreter.set_main_file(Path::new(file_path));
+                if !self.execute(contents) {
+                    exit(70);
+                }
+            }
             Err(_) => print_error_msg("Unable to read the file"),
         }
     }
@@ -47,20 +54,25 @@ impl Rox {
             let mut line = String::new();
             match std::io::stdin().read_line(&mut line) {
                 Ok(_) if (self.is_end(&line)) => exit(0),
-                Ok(_) => self.execute(line),
+                Ok(_) => {
+                    self.execute(line);
+                }
                 Err(_) => print_error_msg("Unable to read the line"),
             }
         }
     }
 
-    fn execute(&mut self, contents: String) {
+    fn execute(&mut self, contents: String) -> bool {
         let mut scanner = lexer::scanner::Scanner::new(&contents);
         let tokens = scanner.scan_tokens().clone();
 
         let mut parser = parser::parser::Parser::new(tokens);
         let stmts = parser.parse();
+        if scanner.had_error() || parser.had_error() {
+            return false;
+        }
 
-        self.interpreter.interpret(&stmts);
+        self.interpreter.interpret(&stmts)
     }
 
     fn is_end(&self, line: &str) -> bool {
@@ -69,8 +81,15 @@ impl Rox {
 }
 
 fn main() {
-    let mut rox = Rox::new();
-    rox.run();
+    // Deep recursion in scripts recurses in the interpreter too, so run it on
+    // a thread with a stack big enough to reach the call depth limit.
+    let runner = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(|| Rox::new().run())
+        .expect("failed to start the interpreter thread");
+    if runner.join().is_err() {
+        exit(70);
+    }
 }
 
 fn error(line_number: u32, message: &str) {
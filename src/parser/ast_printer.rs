@@ -1,5 +1,9 @@
 struct AstPrinter;
-use super::expr::{Acceptor, Expr, Operator, Visitor};
+use super::{
+    expr::{Acceptor, Expr, Operator, Visitor},
+    pattern::MatchArm,
+    stmt::Stmt,
+};
 use crate::lexer::{token, token::Token};
 
 impl AstPrinter {
@@ -21,7 +25,13 @@ impl AstPrinter {
 }
 
 impl Visitor<String> for AstPrinter {
-    fn visit_binary(&mut self, left: &Expr, operator: &Operator, right: &Expr) -> String {
+    fn visit_binary(
+        &mut self,
+        left: &Expr,
+        operator: &Operator,
+        _token: &Token,
+        right: &Expr,
+    ) -> String {
         self.parenthesize(operator.to_string(), vec![left.clone(), right.clone()])
     }
 
@@ -33,7 +43,7 @@ impl Visitor<String> for AstPrinter {
         expr.to_string()
     }
 
-    fn visit_unary(&mut self, operator: &Operator, right: &Expr) -> String {
+    fn visit_unary(&mut self, operator: &Operator, _token: &Token, right: &Expr) -> String {
         self.parenthesize(operator.to_string(), vec![right.clone()])
     }
 
@@ -48,11 +58,120 @@ impl Visitor<String> for AstPrinter {
     fn visit_logical(&mut self, left: &Expr, operator: &Operator, right: &Expr) -> String {
         self.parenthesize(operator.to_string(), vec![left.clone(), right.clone()])
     }
+
+    fn visit_conditional(
+        &mut self,
+        condition: &Expr,
+        then_branch: &Expr,
+        else_branch: &Expr,
+    ) -> String {
+        self.parenthesize(
+            "?:".to_string(),
+            vec![condition.clone(), then_branch.clone(), else_branch.clone()],
+        )
+    }
+
+    fn visit_call(&mut self, callee: &Expr, _paren: &Token, arguments: &[Expr]) -> String {
+        let mut exprs = vec![callee.clone()];
+        exprs.extend(arguments.iter().cloned());
+        self.parenthesize("call".to_string(), exprs)
+    }
+
+    fn visit_get(&mut self, object: &Expr, name: &Token) -> String {
+        self.parenthesize(format!(". {}", name.lexeme), vec![object.clone()])
+    }
+
+    fn visit_set(&mut self, object: &Expr, name: &Token, value: &Expr) -> String {
+        self.parenthesize(
+            format!("= {}", name.lexeme),
+            vec![object.clone(), value.clone()],
+        )
+    }
+
+    fn visit_this(&mut self, _keyword: &Token) -> String {
+        "this".to_string()
+    }
+
+    fn visit_super(&mut self, _keyword: &Token, method: &Token) -> String {
+        format!("(super {})", method.lexeme)
+    }
+
+    fn visit_lambda(&mut self, _keyword: &Token, params: &[Token], _body: &[Stmt]) -> String {
+        let params = params
+            .iter()
+            .map(|p| p.lexeme.clone())
+            .collect::<Vec<String>>();
+        format!("(fun ({}))", params.join(" "))
+    }
+
+    fn visit_list(&mut self, elements: &[Expr]) -> String {
+        self.parenthesize("list".to_string(), elements.to_vec())
+    }
+
+    fn visit_map(&mut self, _brace: &Token, entries: &[(Expr, Expr)]) -> String {
+        let exprs = entries
+            .iter()
+            .flat_map(|(k, v)| [k.clone(), v.clone()])
+            .collect();
+        self.parenthesize("map".to_string(), exprs)
+    }
+
+    fn visit_index(&mut self, object: &Expr, _bracket: &Token, index: &Expr) -> String {
+        self.parenthesize("[]".to_string(), vec![object.clone(), index.clone()])
+    }
+
+    fn visit_index_set(
+        &mut self,
+        object: &Expr,
+        _bracket: &Token,
+        index: &Expr,
+        value: &Expr,
+    ) -> String {
+        self.parenthesize(
+            "[]=".to_string(),
+            vec![object.clone(), index.clone(), value.clone()],
+        )
+    }
+
+    fn visit_interpolation(&mut self, _start: &Token, parts: &[Expr]) -> String {
+        self.parenthesize("interpolate".to_string(), parts.to_vec())
+    }
+
+    fn visit_compound_assignment(
+        &mut self,
+        target: &Expr,
+        _operator: &Operator,
+        token: &Token,
+        value: &Expr,
+    ) -> String {
+        self.parenthesize(token.lexeme.clone(), vec![target.clone(), value.clone()])
+    }
+
+    fn visit_increment(
+        &mut self,
+        target: &Expr,
+        _operator: &Operator,
+        token: &Token,
+        prefix: bool,
+    ) -> String {
+        let name = if prefix {
+            format!("{} prefix", token.lexeme)
+        } else {
+            format!("{} postfix", token.lexeme)
+        };
+        self.parenthesize(name, vec![target.clone()])
+    }
+
+    fn visit_match(&mut self, _keyword: &Token, subject: &Expr, arms: &[MatchArm]) -> String {
+        let arms: Vec<String> = arms.iter().map(|arm| format!("({})", arm)).collect();
+        format!("(match {} {})", subject.accept(self), arms.join(" "))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::lexer::token_type::TokenType;
     use crate::parser::expr::Operator;
 
     #[test]
@@ -63,11 +182,13 @@ mod tests {
                     value: token::Literal::Int(1),
                 }),
                 operator: Operator::Plus,
+                token: Token::new(TokenType::PLUS, "+".to_string(), None, 1),
                 right: Box::new(Expr::Literal {
                     value: token::Literal::Int(2),
                 }),
             }),
             operator: Operator::Star,
+            token: Token::new(TokenType::STAR, "*".to_string(), None, 1),
             right: Box::new(Expr::Literal {
                 value: token::Literal::Int(3),
             }),
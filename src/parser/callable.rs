@@ -0,0 +1,131 @@
+use core::fmt;
+use std::{cell::RefCell, path::PathBuf, rc::Rc};
+
+use crate::lexer::{token::Token, token_type::TokenType};
+
+use super::{
+    environment::Environment,
+    interpreter::{Interpreter, Object, Result, Unwind},
+    stmt::Stmt,
+};
+
+pub struct Function {
+    /// `None` for anonymous function expressions.
+    name: Option<Token>,
+    params: Vec<Token>,
+    body: Vec<Stmt>,
+    closure: Rc<RefCell<Environment>>,
+    is_initializer: bool,
+    /// The file the function was declared in, which imports in its body
+    /// resolve against.
+    file: Option<PathBuf>,
+}
+
+impl Function {
+    pub fn new(
+        name: Option<&Token>,
+        params: &[Token],
+        body: &[Stmt],
+        closure: Rc<RefCell<Environment>>,
+        is_initializer: bool,
+        file: Option<PathBuf>,
+    ) -> Function {
+        Function {
+            name: name.cloned(),
+            params: params.to_vec(),
+            body: body.to_vec(),
+            closure,
+            is_initializer,
+            file,
+        }
+    }
+
+    /// Returns a copy of this method whose closure has `this` bound to `instance`.
+    pub fn bind(&self, instance: Object) -> Function {
+        let mut environment = Environment::new_enclosed(self.closure.clone());
+        environment.define("this".to_string(), instance);
+        Function {
+            name: self.name.clone(),
+            params: self.params.clone(),
+            body: self.body.clone(),
+            closure: Rc::new(RefCell::new(environment)),
+            is_initializer: self.is_initializer,
+            file: self.file.clone(),
+        }
+    }
+
+    pub fn arity(&self) -> usize {
+        self.params.len()
+    }
+
+    pub fn call(&self, interpreter: &mut Interpreter, arguments: Vec<Object>) -> Result<Object> {
+        let mut environment = Environment::new_enclosed(self.closure.clone());
+        for (param, argument) in self.params.iter().zip(arguments) {
+            environment.define(param.lexeme.clone(), argument);
+        }
+        let caller = interpreter.swap_current_file(self.file.clone());
+        let result = interpreter.execute_block(&self.body, Rc::new(RefCell::new(environment)));
+        interpreter.swap_current_file(caller);
+        match result {
+            Ok(_) | Err(Unwind::Return(_)) if self.is_initializer => self.this(),
+            Ok(_) => Ok(Object::Nil),
+            Err(Unwind::Return(value)) => Ok(value),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn this(&self) -> Result<Object> {
+        let line = self.name.as_ref().map_or(0, |name| name.line);
+        let this = Token::new(TokenType::THIS, "this".to_string(), None, line);
+        self.closure.borrow().get(&this)
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "<fn {}>", name.lexeme),
+            None => write!(f, "<anonymous fn>"),
+        }
+    }
+}
+
+/// A function implemented in Rust and exposed to scripts as a global.
+pub struct NativeFunction {
+    name: &'static str,
+    arity: usize,
+    function: fn(&Token, Vec<Object>) -> Result<Object>,
+}
+
+impl NativeFunction {
+    pub fn new(
+        name: &'static str,
+        arity: usize,
+        function: fn(&Token, Vec<Object>) -> Result<Object>,
+    ) -> NativeFunction {
+        NativeFunction {
+            name,
+            arity,
+            function,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+
+    /// `paren` is the closing parenthesis of the call, used to report errors.
+    pub fn call(&self, paren: &Token, arguments: Vec<Object>) -> Result<Object> {
+        (self.function)(paren, arguments)
+    }
+}
+
+impl fmt::Display for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<native fn {}>", self.name)
+    }
+}
@@ -0,0 +1,130 @@
+use core::fmt;
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::lexer::token::Token;
+
+use super::{
+    callable::Function,
+    interpreter::{Interpreter, InterpreterError, Object, Result},
+};
+
+pub struct Class {
+    name: String,
+    superclass: Option<Rc<Class>>,
+    methods: HashMap<String, Rc<Function>>,
+    /// Set on the built-in `Error` class and inherited by its subclasses.
+    is_error: bool,
+}
+
+impl Class {
+    pub fn new(
+        name: &str,
+        superclass: Option<Rc<Class>>,
+        methods: HashMap<String, Rc<Function>>,
+    ) -> Class {
+        Class {
+            name: name.to_string(),
+            is_error: superclass
+                .as_ref()
+                .is_some_and(|superclass| superclass.is_error),
+            superclass,
+            methods,
+        }
+    }
+
+    /// Creates the built-in `Error` class.
+    pub fn new_error(methods: HashMap<String, Rc<Function>>) -> Class {
+        Class {
+            is_error: true,
+            ..Class::new("Error", None, methods)
+        }
+    }
+
+    /// Looks up `name` on this class, then on each superclass in turn.
+    pub fn find_method(&self, name: &str) -> Option<Rc<Function>> {
+        match self.methods.get(name) {
+            Some(method) => Some(method.clone()),
+            None => self
+                .superclass
+                .as_ref()
+                .and_then(|superclass| superclass.find_method(name)),
+        }
+    }
+
+    pub fn arity(&self) -> usize {
+        self.find_method("init").map_or(0, |init| init.arity())
+    }
+
+    pub fn call(
+        class: &Rc<Class>,
+        interpreter: &mut Interpreter,
+        arguments: Vec<Object>,
+    ) -> Result<Object> {
+        let instance = Object::Instance(Rc::new(RefCell::new(Instance::new(class.clone()))));
+        if let Some(initializer) = class.find_method("init") {
+            initializer
+                .bind(instance.clone())
+                .call(interpreter, arguments)?;
+        }
+        Ok(instance)
+    }
+}
+
+impl fmt::Display for Class {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<class {}>", self.name)
+    }
+}
+
+pub struct Instance {
+    class: Rc<Class>,
+    fields: HashMap<String, Object>,
+}
+
+impl Instance {
+    pub fn new(class: Rc<Class>) -> Instance {
+        Instance {
+            class,
+            fields: HashMap::new(),
+        }
+    }
+
+    pub fn get(instance: &Rc<RefCell<Instance>>, name: &Token) -> Result<Object> {
+        if let Some(value) = instance.borrow().fields.get(&name.lexeme) {
+            return Ok(value.clone());
+        }
+        let method = instance.borrow().class.find_method(&name.lexeme);
+        match method {
+            Some(method) => Ok(Object::Function(Rc::new(
+                method.bind(Object::Instance(instance.clone())),
+            ))),
+            None => Err(InterpreterError::new(
+                name,
+                &format!("Undefined property '{}'.", name.lexeme),
+            )
+            .into()),
+        }
+    }
+
+    pub fn field(&self, name: &str) -> Option<Object> {
+        self.fields.get(name).cloned()
+    }
+
+    /// Whether this is an instance of `Error` or one of its subclasses.
+    pub fn is_error(&self) -> bool {
+        self.class.is_error
+    }
+
+    pub fn set(&mut self, name: &Token, value: Object) {
+        self.fields.insert(name.lexeme.clone(), value);
+    }
+}
+
+impl fmt::Display for Instance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.fields.get("message") {
+            Some(message) if self.class.is_error => write!(f, "{}: {}", self.class.name, message),
+            _ => write!(f, "<{} instance>", self.class.name),
+        }
+    }
+}
@@ -1,13 +1,14 @@
-use std::collections;
+use std::{cell::RefCell, collections, rc::Rc};
 
-use crate::{lexer::token::Token, log};
+use crate::lexer::token::Token;
 
-use super::interpreter::Object;
+use super::interpreter::{InterpreterError, Object, Result};
 
-#[derive(Clone)]
 pub struct Environment {
-    enclosing: Option<Box<Environment>>,
+    enclosing: Option<Rc<RefCell<Environment>>>,
     values: collections::HashMap<String, Object>,
+    /// The names in `values` that were declared with `const`.
+    constants: collections::HashSet<String>,
 }
 
 impl Environment {
@@ -15,13 +16,15 @@ impl Environment {
         Environment {
             enclosing: None,
             values: collections::HashMap::new(),
+            constants: collections::HashSet::new(),
         }
     }
 
-    pub fn new_enclosed(env: Environment) -> Environment {
+    pub fn new_enclosed(env: Rc<RefCell<Environment>>) -> Environment {
         Environment {
-            enclosing: Some(Box::new(env)),
+            enclosing: Some(env),
             values: collections::HashMap::new(),
+            constants: collections::HashSet::new(),
         }
     }
 
@@ -29,28 +32,53 @@ impl Environment {
         self.values.insert(name, value);
     }
 
-    pub fn get(&self, name: &Token) -> Object {
+    /// Binds a declared name. Like the parser, this lets a declaration shadow
+    /// a constant from an enclosing scope but not replace one in this scope,
+    /// which the parser can't see across separate REPL lines.
+    pub fn declare(&mut self, name: &Token, value: Object, constant: bool) -> Result<()> {
+        if self.constants.contains(&name.lexeme) {
+            return Err(InterpreterError::new(
+                name,
+                &format!("Can't redeclare constant '{}'.", name.lexeme),
+            )
+            .into());
+        }
+        if constant {
+            self.constants.insert(name.lexeme.clone());
+        }
+        self.values.insert(name.lexeme.clone(), value);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &Token) -> Result<Object> {
         match self.values.get(&name.lexeme) {
-            Some(v) => v.clone(),
+            Some(v) => Ok(v.clone()),
             None => match self.enclosing {
-                Some(ref e) => e.get(name),
-                None => Object::Nil,
+                Some(ref e) => e.borrow().get(name),
+                None => Err(undefined(name)),
             },
         }
     }
 
-    pub fn assign(&mut self, var: &Token, expr: Object) {
+    pub fn assign(&mut self, var: &Token, expr: Object) -> Result<()> {
         match self.values.get_mut(&var.lexeme) {
-            Some(v) => *v = expr,
-            None => {
-                if let Some(ref mut e) = self.enclosing {
-                    return e.assign(var, expr);
-                }
-                log::log_message::print_code_error(
-                    var.line,
-                    &format!("Undefined variable '{}'", var.lexeme),
-                );
+            Some(_) if self.constants.contains(&var.lexeme) => Err(InterpreterError::new(
+                var,
+                &format!("Can't assign to constant '{}'.", var.lexeme),
+            )
+            .into()),
+            Some(v) => {
+                *v = expr;
+                Ok(())
             }
+            None => match self.enclosing {
+                Some(ref e) => e.borrow_mut().assign(var, expr),
+                None => Err(undefined(var)),
+            },
         }
     }
 }
+
+fn undefined(name: &Token) -> super::interpreter::Unwind {
+    InterpreterError::new(name, &format!("Undefined variable '{}'.", name.lexeme)).into()
+}
@@ -5,14 +5,43 @@ use crate::lexer::{
     token_type,
 };
 
+use super::{pattern::MatchArm, stmt::Stmt};
+
 pub trait Visitor<T> {
-    fn visit_binary(&mut self, left: &Expr, operator: &Operator, right: &Expr) -> T;
+    fn visit_binary(&mut self, left: &Expr, operator: &Operator, token: &Token, right: &Expr) -> T;
     fn visit_grouping(&mut self, expression: &Expr) -> T;
     fn visit_literal(&mut self, expr: &token::Literal) -> T;
-    fn visit_unary(&mut self, operator: &Operator, right: &Expr) -> T;
+    fn visit_unary(&mut self, operator: &Operator, token: &Token, right: &Expr) -> T;
     fn visit_variable(&mut self, name: &Token) -> T;
     fn visit_assignment(&mut self, name: &Token, value: &Expr) -> T;
     fn visit_logical(&mut self, left: &Expr, operator: &Operator, right: &Expr) -> T;
+    fn visit_conditional(&mut self, condition: &Expr, then_branch: &Expr, else_branch: &Expr) -> T;
+    fn visit_call(&mut self, callee: &Expr, paren: &Token, arguments: &[Expr]) -> T;
+    fn visit_get(&mut self, object: &Expr, name: &Token) -> T;
+    fn visit_set(&mut self, object: &Expr, name: &Token, value: &Expr) -> T;
+    fn visit_this(&mut self, keyword: &Token) -> T;
+    fn visit_super(&mut self, keyword: &Token, method: &Token) -> T;
+    fn visit_lambda(&mut self, keyword: &Token, params: &[Token], body: &[Stmt]) -> T;
+    fn visit_list(&mut self, elements: &[Expr]) -> T;
+    fn visit_map(&mut self, brace: &Token, entries: &[(Expr, Expr)]) -> T;
+    fn visit_index(&mut self, object: &Expr, bracket: &Token, index: &Expr) -> T;
+    fn visit_index_set(&mut self, object: &Expr, bracket: &Token, index: &Expr, value: &Expr) -> T;
+    fn visit_interpolation(&mut self, start: &Token, parts: &[Expr]) -> T;
+    fn visit_compound_assignment(
+        &mut self,
+        target: &Expr,
+        operator: &Operator,
+        token: &Token,
+        value: &Expr,
+    ) -> T;
+    fn visit_increment(
+        &mut self,
+        target: &Expr,
+        operator: &Operator,
+        token: &Token,
+        prefix: bool,
+    ) -> T;
+    fn visit_match(&mut self, keyword: &Token, subject: &Expr, arms: &[MatchArm]) -> T;
 }
 
 pub trait Acceptor<T> {
@@ -25,13 +54,16 @@ pub enum Expr {
         name: token::Token,
         value: Box<Expr>,
     },
+    /// `token` is the operator as written in the source, kept for error reporting.
     Binary {
         left: Box<Expr>,
         operator: Operator,
+        token: token::Token,
         right: Box<Expr>,
     },
     Unary {
         operator: Operator,
+        token: token::Token,
         right: Box<Expr>,
     },
     Grouping {
@@ -48,6 +80,87 @@ pub enum Expr {
         operator: Operator,
         right: Box<Expr>,
     },
+    /// `condition ? then_branch : else_branch`
+    Conditional {
+        condition: Box<Expr>,
+        then_branch: Box<Expr>,
+        else_branch: Box<Expr>,
+    },
+    Call {
+        callee: Box<Expr>,
+        paren: token::Token,
+        arguments: Vec<Expr>,
+    },
+    Get {
+        object: Box<Expr>,
+        name: token::Token,
+    },
+    Set {
+        object: Box<Expr>,
+        name: token::Token,
+        value: Box<Expr>,
+    },
+    This {
+        keyword: token::Token,
+    },
+    Super {
+        keyword: token::Token,
+        method: token::Token,
+    },
+    Lambda {
+        keyword: token::Token,
+        params: Vec<token::Token>,
+        body: Vec<Stmt>,
+    },
+    List {
+        elements: Vec<Expr>,
+    },
+    /// `brace` is the opening '{', kept for error reporting.
+    Map {
+        brace: token::Token,
+        entries: Vec<(Expr, Expr)>,
+    },
+    /// `bracket` is the closing ']' of the subscript, kept for error reporting.
+    Index {
+        object: Box<Expr>,
+        bracket: token::Token,
+        index: Box<Expr>,
+    },
+    IndexSet {
+        object: Box<Expr>,
+        bracket: token::Token,
+        index: Box<Expr>,
+        value: Box<Expr>,
+    },
+    /// The string literal pieces and embedded expressions of `"a ${b} c"`, in
+    /// source order.
+    Interpolation {
+        /// The first piece of the string, kept for error reporting.
+        start: token::Token,
+        parts: Vec<Expr>,
+    },
+    /// `target op= value`, where `target` is a variable, property or index and
+    /// `token` is the compound operator as written.
+    CompoundAssignment {
+        target: Box<Expr>,
+        operator: Operator,
+        token: token::Token,
+        value: Box<Expr>,
+    },
+    /// `++target`, `target--` and so on; `operator` is `Plus` or `Minus`.
+    Increment {
+        target: Box<Expr>,
+        operator: Operator,
+        token: token::Token,
+        prefix: bool,
+    },
+    /// `keyword` is the `match` keyword, kept for reporting a value no arm
+    /// matches.
+    Match {
+        keyword: token::Token,
+        subject: Box<Expr>,
+        arms: Vec<MatchArm>,
+    },
 }
 
 impl fmt::Display for Expr {
@@ -58,8 +171,11 @@ impl fmt::Display for Expr {
                 left,
                 operator,
                 right,
+                ..
             } => write!(f, "({} {} {})", left, operator, right),
-            Expr::Unary { operator, right } => write!(f, "({}{})", operator, right),
+            Expr::Unary {
+                operator, right, ..
+            } => write!(f, "({}{})", operator, right),
             Expr::Grouping { expression } => write!(f, "({})", expression),
             Expr::Literal { value } => write!(f, "{}", value),
             Expr::Variable { name } => write!(f, "{}", name.lexeme),
@@ -68,6 +184,99 @@ impl fmt::Display for Expr {
                 operator,
                 right,
             } => write!(f, "({} {} {})", left, operator, right),
+            Expr::Conditional {
+                condition,
+                then_branch,
+                else_branch,
+            } => write!(f, "({} ? {} : {})", condition, then_branch, else_branch),
+            Expr::Call {
+                callee, arguments, ..
+            } => write!(
+                f,
+                "{}({})",
+                callee,
+                arguments
+                    .iter()
+                    .map(|a| a.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Expr::Get { object, name } => write!(f, "{}.{}", object, name.lexeme),
+            Expr::Set {
+                object,
+                name,
+                value,
+            } => write!(f, "({}.{} = {})", object, name.lexeme, value),
+            Expr::This { .. } => write!(f, "this"),
+            Expr::Super { method, .. } => write!(f, "super.{}", method.lexeme),
+            Expr::Lambda { params, .. } => write!(
+                f,
+                "fun ({})",
+                params
+                    .iter()
+                    .map(|p| p.lexeme.clone())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Expr::List { elements } => write!(
+                f,
+                "[{}]",
+                elements
+                    .iter()
+                    .map(|e| e.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Expr::Map { entries, .. } => write!(
+                f,
+                "{{{}}}",
+                entries
+                    .iter()
+                    .map(|(k, v)| format!("{}: {}", k, v))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Expr::Index { object, index, .. } => write!(f, "{}[{}]", object, index),
+            Expr::IndexSet {
+                object,
+                index,
+                value,
+                ..
+            } => write!(f, "({}[{}] = {})", object, index, value),
+            Expr::Interpolation { parts, .. } => {
+                write!(f, "\"")?;
+                for part in parts {
+                    match part {
+                        Expr::Literal {
+                            value: token::Literal::String(s),
+                        } => write!(f, "{}", s)?,
+                        _ => write!(f, "${{{}}}", part)?,
+                    }
+                }
+                write!(f, "\"")
+            }
+            Expr::CompoundAssignment {
+                target,
+                token,
+                value,
+                ..
+            } => write!(f, "({} {} {})", target, token.lexeme, value),
+            Expr::Increment {
+                target,
+                token,
+                prefix: true,
+                ..
+            } => write!(f, "({}{})", token.lexeme, target),
+            Expr::Increment { target, token, .. } => write!(f, "({}{})", target, token.lexeme),
+            Expr::Match { subject, arms, .. } => write!(
+                f,
+                "match ({}) {{ {} }}",
+                subject,
+                arms.iter()
+                    .map(|a| a.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
         }
     }
 }
@@ -79,6 +288,15 @@ pub enum Operator {
     Plus,
     Slash,
     Star,
+    Percent,
+    StarStar,
+    TildeSlash,
+    Ampersand,
+    Pipe,
+    Caret,
+    Tilde,
+    LessLess,
+    GreaterGreater,
     Greater,
     GreaterEqual,
     Less,
@@ -87,6 +305,8 @@ pub enum Operator {
     BangEqual,
     Or,
     And,
+    Coalesce,
+    In,
 }
 
 impl From<Token> for Operator {
@@ -97,6 +317,15 @@ impl From<Token> for Operator {
             token_type::TokenType::PLUS => Operator::Plus,
             token_type::TokenType::SLASH => Operator::Slash,
             token_type::TokenType::STAR => Operator::Star,
+            token_type::TokenType::PERCENT => Operator::Percent,
+            token_type::TokenType::STAR_STAR => Operator::StarStar,
+            token_type::TokenType::TILDE_SLASH => Operator::TildeSlash,
+            token_type::TokenType::AMPERSAND => Operator::Ampersand,
+            token_type::TokenType::PIPE => Operator::Pipe,
+            token_type::TokenType::CARET => Operator::Caret,
+            token_type::TokenType::TILDE => Operator::Tilde,
+            token_type::TokenType::LESS_LESS => Operator::LessLess,
+            token_type::TokenType::GREATER_GREATER => Operator::GreaterGreater,
             token_type::TokenType::GREATER => Operator::Greater,
             token_type::TokenType::GREATER_EQUAL => Operator::GreaterEqual,
             token_type::TokenType::LESS => Operator::Less,
@@ -105,6 +334,16 @@ impl From<Token> for Operator {
             token_type::TokenType::BANG_EQUAL => Operator::BangEqual,
             token_type::TokenType::OR => Operator::Or,
             token_type::TokenType::AND => Operator::And,
+            token_type::TokenType::QUESTION_QUESTION => Operator::Coalesce,
+            token_type::TokenType::IN => Operator::In,
+            // Compound assignments apply the operator they are built on.
+            token_type::TokenType::PLUS_EQUAL | token_type::TokenType::PLUS_PLUS => Operator::Plus,
+            token_type::TokenType::MINUS_EQUAL | token_type::TokenType::MINUS_MINUS => {
+                Operator::Minus
+            }
+            token_type::TokenType::STAR_EQUAL => Operator::Star,
+            token_type::TokenType::SLASH_EQUAL => Operator::Slash,
+            token_type::TokenType::PERCENT_EQUAL => Operator::Percent,
             _ => unreachable!(),
         }
     }
@@ -118,6 +357,15 @@ impl fmt::Display for Operator {
             Operator::Plus => write!(f, "+"),
             Operator::Slash => write!(f, "/"),
             Operator::Star => write!(f, "*"),
+            Operator::Percent => write!(f, "%"),
+            Operator::StarStar => write!(f, "**"),
+            Operator::TildeSlash => write!(f, "~/"),
+            Operator::Ampersand => write!(f, "&"),
+            Operator::Pipe => write!(f, "|"),
+            Operator::Caret => write!(f, "^"),
+            Operator::Tilde => write!(f, "~"),
+            Operator::LessLess => write!(f, "<<"),
+            Operator::GreaterGreater => write!(f, ">>"),
             Operator::Greater => write!(f, ">"),
             Operator::GreaterEqual => write!(f, ">="),
             Operator::Less => write!(f, "<"),
@@ -126,6 +374,8 @@ impl fmt::Display for Operator {
             Operator::BangEqual => write!(f, "!="),
             Operator::Or => write!(f, "or"),
             Operator::And => write!(f, "and"),
+            Operator::Coalesce => write!(f, "??"),
+            Operator::In => write!(f, "in"),
         }
     }
 }
@@ -137,9 +387,14 @@ impl<T> Acceptor<T> for Expr {
             Expr::Binary {
                 left,
                 operator,
+                token,
+                right,
+            } => visitor.visit_binary(left, operator, token, right),
+            Expr::Unary {
+                operator,
+                token,
                 right,
-            } => visitor.visit_binary(left, operator, right),
-            Expr::Unary { operator, right } => visitor.visit_unary(operator, right),
+            } => visitor.visit_unary(operator, token, right),
             Expr::Grouping { expression } => visitor.visit_grouping(expression),
             Expr::Literal { value } => visitor.visit_literal(value),
             Expr::Variable { name } => visitor.visit_variable(name),
@@ -148,6 +403,60 @@ impl<T> Acceptor<T> for Expr {
                 operator,
                 right,
             } => visitor.visit_logical(left, operator, right),
+            Expr::Conditional {
+                condition,
+                then_branch,
+                else_branch,
+            } => visitor.visit_conditional(condition, then_branch, else_branch),
+            Expr::Call {
+                callee,
+                paren,
+                arguments,
+            } => visitor.visit_call(callee, paren, arguments),
+            Expr::Get { object, name } => visitor.visit_get(object, name),
+            Expr::Set {
+                object,
+                name,
+                value,
+            } => visitor.visit_set(object, name, value),
+            Expr::This { keyword } => visitor.visit_this(keyword),
+            Expr::Super { keyword, method } => visitor.visit_super(keyword, method),
+            Expr::Lambda {
+                keyword,
+                params,
+                body,
+            } => visitor.visit_lambda(keyword, params, body),
+            Expr::List { elements } => visitor.visit_list(elements),
+            Expr::Map { brace, entries } => visitor.visit_map(brace, entries),
+            Expr::Index {
+                object,
+                bracket,
+                index,
+            } => visitor.visit_index(object, bracket, index),
+            Expr::IndexSet {
+                object,
+                bracket,
+                index,
+                value,
+            } => visitor.visit_index_set(object, bracket, index, value),
+            Expr::Interpolation { start, parts } => visitor.visit_interpolation(start, parts),
+            Expr::CompoundAssignment {
+                target,
+                operator,
+                token,
+                value,
+            } => visitor.visit_compound_assignment(target, operator, token, value),
+            Expr::Increment {
+                target,
+                operator,
+                token,
+                prefix,
+            } => visitor.visit_increment(target, operator, token, *prefix),
+            Expr::Match {
+                keyword,
+                subject,
+                arms,
+            } => visitor.visit_match(keyword, subject, arms),
         }
     }
 }
@@ -1,27 +1,163 @@
-use std::fmt;
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
 
 use crate::{
-    lexer::token::{Literal, Token},
+    lexer::{
+        scanner::Scanner,
+        token::{Literal, Token},
+        token_type::TokenType,
+    },
     log,
 };
 
 use super::{
+    callable::{Function, NativeFunction},
+    class::{Class, Instance},
     environment,
     expr::{Acceptor, Expr, Operator, Visitor},
+    map::{Map, MapKey},
+    module::{self, Module},
+    natives,
+    parser::Parser,
+    pattern::{ArmBody, MatchArm},
     stmt::{self, Acceptor as StmtAcceptor, Stmt},
 };
 
 #[derive(Clone)]
 pub enum Object {
     String(String),
-    Int(i32),
+    Int(i64),
     Boolean(bool),
     Float(f64),
     Nil,
     Identifier(String),
+    Function(Rc<Function>),
+    NativeFunction(Rc<NativeFunction>),
+    Class(Rc<Class>),
+    Instance(Rc<RefCell<Instance>>),
+    List(Rc<RefCell<Vec<Object>>>),
+    Map(Rc<RefCell<Map>>),
+    Module(Rc<Module>),
 }
 
 impl Object {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Object::String(_) => "string",
+            Object::Int(_) => "int",
+            Object::Boolean(_) => "boolean",
+            Object::Float(_) => "float",
+            Object::Nil => "nil",
+            Object::Identifier(_) => "identifier",
+            Object::Function(_) | Object::NativeFunction(_) => "function",
+            Object::Class(_) => "class",
+            Object::Instance(_) => "instance",
+            Object::List(_) => "list",
+            Object::Map(_) => "map",
+            Object::Module(_) => "module",
+        }
+    }
+
+    /// Like `to_string`, but quotes strings so they read unambiguously inside
+    /// collections. Collections nested too deeply to print show as `...`.
+    pub fn repr(&self) -> String {
+        self.repr_within(&mut Vec::new())
+            .unwrap_or_else(|| "...".to_string())
+    }
+
+    /// `repr` for a value nested inside the collections in `seen`, which are
+    /// the ones still being printed. One that contains itself prints the
+    /// repeat as `[...]` or `{...}` instead of recursing forever. `None` if
+    /// collections are nested more than `MAX_NESTING_DEPTH` deep.
+    pub fn repr_within(&self, seen: &mut Vec<*const ()>) -> Option<String> {
+        match self {
+            Object::String(s) => Some(format!("{:?}", s)),
+            Object::List(list) => {
+                let address = Rc::as_ptr(list) as *const ();
+                if seen.contains(&address) {
+                    return Some("[...]".to_string());
+                }
+                if seen.len() == MAX_NESTING_DEPTH {
+                    return None;
+                }
+                seen.push(address);
+                let elements = list
+                    .borrow()
+                    .iter()
+                    .map(|e| e.repr_within(seen))
+                    .collect::<Option<Vec<String>>>();
+                seen.pop();
+                Some(format!("[{}]", elements?.join(", ")))
+            }
+            Object::Map(map) => {
+                let address = Rc::as_ptr(map) as *const ();
+                if seen.contains(&address) {
+                    return Some("{...}".to_string());
+                }
+                if seen.len() == MAX_NESTING_DEPTH {
+                    return None;
+                }
+                seen.push(address);
+                let entries = map.borrow().repr_within(seen);
+                seen.pop();
+                entries
+            }
+            _ => Some(self.to_string()),
+        }
+    }
+
+    /// `==` for a value nested inside the collections in `comparing`, which
+    /// are the pairs still being compared. A pair met again is taken to be
+    /// equal, so comparing collections that contain themselves terminates.
+    /// `None` if collections are nested more than `MAX_NESTING_DEPTH` deep.
+    pub fn equals(
+        &self,
+        other: &Object,
+        comparing: &mut Vec<(*const (), *const ())>,
+    ) -> Option<bool> {
+        match (self, other) {
+            (Object::List(l), Object::List(r)) => {
+                let pair = (Rc::as_ptr(l) as *const (), Rc::as_ptr(r) as *const ());
+                if Rc::ptr_eq(l, r) || comparing.contains(&pair) {
+                    return Some(true);
+                }
+                if comparing.len() == MAX_NESTING_DEPTH {
+                    return None;
+                }
+                comparing.push(pair);
+                let (l, r) = (l.borrow(), r.borrow());
+                let mut equal = Some(l.len() == r.len());
+                for (l, r) in l.iter().zip(r.iter()) {
+                    if equal != Some(true) {
+                        break;
+                    }
+                    equal = l.equals(r, comparing);
+                }
+                comparing.pop();
+                equal
+            }
+            (Object::Map(l), Object::Map(r)) => {
+                let pair = (Rc::as_ptr(l) as *const (), Rc::as_ptr(r) as *const ());
+                if Rc::ptr_eq(l, r) || comparing.contains(&pair) {
+                    return Some(true);
+                }
+                if comparing.len() == MAX_NESTING_DEPTH {
+                    return None;
+                }
+                comparing.push(pair);
+                let equal = l.borrow().equals(&r.borrow(), comparing);
+                comparing.pop();
+                equal
+            }
+            _ => Some(self == other),
+        }
+    }
+
     pub fn is_truthy(&self) -> bool {
         match self {
             Object::Nil => false,
@@ -29,47 +165,510 @@ impl Object {
             Object::Int(i) => *i != 0,
             Object::Float(fl) => *fl != 0.0,
             Object::String(s) => !s.is_empty(),
+            Object::List(l) => !l.borrow().is_empty(),
+            Object::Map(m) => !m.borrow().is_empty(),
             _ => true,
         }
     }
 }
 
+impl From<&Literal> for Object {
+    fn from(literal: &Literal) -> Self {
+        match literal {
+            Literal::String(s) => Object::String(s.clone()),
+            Literal::Int(i) => Object::Int(*i),
+            Literal::Boolean(b) => Object::Boolean(*b),
+            Literal::Float(fl) => Object::Float(*fl),
+            Literal::Nil => Object::Nil,
+            Literal::Identifier(i) => Object::Identifier(i.clone()),
+        }
+    }
+}
+
 impl fmt::Display for Object {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Object::String(s) => write!(f, "{}", s),
             Object::Int(i) => write!(f, "{}", i),
             Object::Boolean(b) => write!(f, "{}", b),
-            Object::Float(fl) => write!(f, "{}", fl),
+            // Debug formatting always keeps a fractional part or exponent, so
+            // floats never print like ints.
+            Object::Float(fl) => write!(f, "{:?}", fl),
             Object::Nil => write!(f, "why am i nil?"),
             Object::Identifier(i) => write!(f, "{}", i),
+            Object::Function(fun) => write!(f, "{}", fun),
+            Object::NativeFunction(fun) => write!(f, "{}", fun),
+            Object::Class(class) => write!(f, "{}", class),
+            Object::Instance(instance) => write!(f, "{}", instance.borrow()),
+            Object::List(_) => write!(f, "{}", self.repr()),
+            Object::Map(_) => write!(f, "{}", self.repr()),
+            Object::Module(module) => write!(f, "{}", module),
+        }
+    }
+}
+
+/// Lists and maps are dropped iteratively, so one nested very deeply doesn't
+/// overflow the native stack on its way out.
+impl Drop for Object {
+    fn drop(&mut self) {
+        let mut pending = Vec::new();
+        take_contents(self, &mut pending);
+        while let Some(mut object) = pending.pop() {
+            take_contents(&mut object, &mut pending);
+        }
+    }
+}
+
+/// Moves the elements of a list or map that is about to be freed onto
+/// `pending`, leaving it empty.
+fn take_contents(object: &mut Object, pending: &mut Vec<Object>) {
+    match object {
+        Object::List(list) if Rc::strong_count(list) == 1 => {
+            pending.append(&mut list.borrow_mut());
+        }
+        Object::Map(map) if Rc::strong_count(map) == 1 => {
+            pending.append(&mut map.borrow_mut().take_values());
+        }
+        _ => {}
+    }
+}
+
+/// Values of different types are never equal, except that an int and a float
+/// compare by numeric value. Lists and maps compare by contents; functions,
+/// classes, instances and modules compare by identity.
+impl PartialEq for Object {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Object::String(l), Object::String(r)) => l == r,
+            (Object::Int(l), Object::Int(r)) => l == r,
+            (Object::Float(l), Object::Float(r)) => l == r,
+            (Object::Int(l), Object::Float(r)) | (Object::Float(r), Object::Int(l)) => {
+                *l as f64 == *r
+            }
+            (Object::Boolean(l), Object::Boolean(r)) => l == r,
+            (Object::Nil, Object::Nil) => true,
+            (Object::Identifier(l), Object::Identifier(r)) => l == r,
+            (Object::Function(l), Object::Function(r)) => Rc::ptr_eq(l, r),
+            (Object::NativeFunction(l), Object::NativeFunction(r)) => Rc::ptr_eq(l, r),
+            (Object::Class(l), Object::Class(r)) => Rc::ptr_eq(l, r),
+            (Object::Instance(l), Object::Instance(r)) => Rc::ptr_eq(l, r),
+            (Object::Module(l), Object::Module(r)) => Rc::ptr_eq(l, r),
+            (Object::List(_), Object::List(_)) | (Object::Map(_), Object::Map(_)) => {
+                self.equals(other, &mut Vec::new()).unwrap_or(false)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The operands of a numeric binary operator. A pair of ints stays integral;
+/// if either side is a float, both are promoted to float.
+enum Numbers {
+    Ints(i64, i64),
+    Floats(f64, f64),
+}
+
+impl Numbers {
+    fn promote(left: &Object, right: &Object) -> Option<Numbers> {
+        match (left, right) {
+            (Object::Int(l), Object::Int(r)) => Some(Numbers::Ints(*l, *r)),
+            (Object::Int(l), Object::Float(r)) => Some(Numbers::Floats(*l as f64, *r)),
+            (Object::Float(l), Object::Int(r)) => Some(Numbers::Floats(*l, *r as f64)),
+            (Object::Float(l), Object::Float(r)) => Some(Numbers::Floats(*l, *r)),
+            _ => None,
         }
     }
 }
 
+/// Applies a binary operator to two evaluated operands. Shared by binary
+/// expressions and compound assignments.
+fn binary_operation(
+    operator: &Operator,
+    token: &Token,
+    left: Object,
+    right: Object,
+) -> Result<Object> {
+    let type_error = || -> Result<Object> {
+        Err(InterpreterError::new(
+            token,
+            &format!(
+                "Unsupported operand types for '{}': {} and {}.",
+                operator,
+                left.type_name(),
+                right.type_name()
+            ),
+        )
+        .into())
+    };
+    let overflow = || -> Unwind {
+        InterpreterError::new(token, &format!("Integer overflow in '{}'.", operator)).into()
+    };
+    match operator {
+        Operator::Plus => match (&left, &right) {
+            (Object::String(l), Object::String(r)) => Ok(Object::String(format!("{}{}", l, r))),
+            _ => match Numbers::promote(&left, &right) {
+                Some(Numbers::Ints(l, r)) => l.checked_add(r).map(Object::Int).ok_or_else(overflow),
+                Some(Numbers::Floats(l, r)) => Ok(Object::Float(l + r)),
+                None => type_error(),
+            },
+        },
+        Operator::Minus => match Numbers::promote(&left, &right) {
+            Some(Numbers::Ints(l, r)) => l.checked_sub(r).map(Object::Int).ok_or_else(overflow),
+            Some(Numbers::Floats(l, r)) => Ok(Object::Float(l - r)),
+            None => type_error(),
+        },
+        Operator::Slash => match Numbers::promote(&left, &right) {
+            Some(Numbers::Ints(_, 0)) => {
+                Err(InterpreterError::new(token, "Division by zero.").into())
+            }
+            Some(Numbers::Ints(l, r)) => l.checked_div(r).map(Object::Int).ok_or_else(overflow),
+            Some(Numbers::Floats(l, r)) => Ok(Object::Float(l / r)),
+            None => type_error(),
+        },
+        Operator::Star => match Numbers::promote(&left, &right) {
+            Some(Numbers::Ints(l, r)) => l.checked_mul(r).map(Object::Int).ok_or_else(overflow),
+            Some(Numbers::Floats(l, r)) => Ok(Object::Float(l * r)),
+            None => type_error(),
+        },
+        Operator::Percent => match Numbers::promote(&left, &right) {
+            Some(Numbers::Ints(_, 0)) => {
+                Err(InterpreterError::new(token, "Division by zero.").into())
+            }
+            Some(Numbers::Ints(l, r)) => Ok(Object::Int(floor_mod(l, r))),
+            Some(Numbers::Floats(l, r)) => Ok(Object::Float(floor_mod_float(l, r))),
+            None => type_error(),
+        },
+        Operator::TildeSlash => match Numbers::promote(&left, &right) {
+            Some(Numbers::Ints(_, 0)) => {
+                Err(InterpreterError::new(token, "Division by zero.").into())
+            }
+            Some(Numbers::Ints(l, r)) => floor_div(l, r).map(Object::Int).ok_or_else(overflow),
+            Some(Numbers::Floats(l, r)) => Ok(Object::Float((l / r).floor())),
+            None => type_error(),
+        },
+        // A negative integer exponent can't produce an int, so it gives a float.
+        Operator::StarStar => match Numbers::promote(&left, &right) {
+            Some(Numbers::Ints(l, r)) if r < 0 => Ok(Object::Float((l as f64).powf(r as f64))),
+            Some(Numbers::Ints(l, r)) => u32::try_from(r)
+                .ok()
+                .and_then(|r| l.checked_pow(r))
+                .map(Object::Int)
+                .ok_or_else(overflow),
+            Some(Numbers::Floats(l, r)) => Ok(Object::Float(l.powf(r))),
+            None => type_error(),
+        },
+        Operator::Ampersand => match (&left, &right) {
+            (Object::Int(l), Object::Int(r)) => Ok(Object::Int(l & r)),
+            _ => type_error(),
+        },
+        Operator::Pipe => match (&left, &right) {
+            (Object::Int(l), Object::Int(r)) => Ok(Object::Int(l | r)),
+            _ => type_error(),
+        },
+        Operator::Caret => match (&left, &right) {
+            (Object::Int(l), Object::Int(r)) => Ok(Object::Int(l ^ r)),
+            _ => type_error(),
+        },
+        // `>>` is arithmetic, so negative numbers stay negative.
+        Operator::LessLess | Operator::GreaterGreater => match (&left, &right) {
+            (Object::Int(l), Object::Int(r)) => {
+                let amount = u32::try_from(*r)
+                    .ok()
+                    .filter(|&r| r < i64::BITS)
+                    .ok_or_else(|| {
+                        InterpreterError::new(
+                            token,
+                            &format!("Shift amount must be between 0 and 63, got {}.", r),
+                        )
+                    })?;
+                Ok(Object::Int(if *operator == Operator::LessLess {
+                    l << amount
+                } else {
+                    l >> amount
+                }))
+            }
+            _ => type_error(),
+        },
+        Operator::Greater => match Numbers::promote(&left, &right) {
+            Some(Numbers::Ints(l, r)) => Ok(Object::Boolean(l > r)),
+            Some(Numbers::Floats(l, r)) => Ok(Object::Boolean(l > r)),
+            None => type_error(),
+        },
+        Operator::GreaterEqual => match Numbers::promote(&left, &right) {
+            Some(Numbers::Ints(l, r)) => Ok(Object::Boolean(l >= r)),
+            Some(Numbers::Floats(l, r)) => Ok(Object::Boolean(l >= r)),
+            None => type_error(),
+        },
+        Operator::Less => match Numbers::promote(&left, &right) {
+            Some(Numbers::Ints(l, r)) => Ok(Object::Boolean(l < r)),
+            Some(Numbers::Floats(l, r)) => Ok(Object::Boolean(l < r)),
+            None => type_error(),
+        },
+        Operator::LessEqual => match Numbers::promote(&left, &right) {
+            Some(Numbers::Ints(l, r)) => Ok(Object::Boolean(l <= r)),
+            Some(Numbers::Floats(l, r)) => Ok(Object::Boolean(l <= r)),
+            None => type_error(),
+        },
+        Operator::In => match &right {
+            Object::Map(map) => Ok(Object::Boolean(
+                MapKey::from_object(&left).is_some_and(|key| map.borrow().contains_key(&key)),
+            )),
+            Object::List(list) => {
+                for element in list.borrow().iter() {
+                    if equals(token, &left, element)? {
+                        return Ok(Object::Boolean(true));
+                    }
+                }
+                Ok(Object::Boolean(false))
+            }
+            _ => type_error(),
+        },
+        Operator::EqualEqual => Ok(Object::Boolean(equals(token, &left, &right)?)),
+        Operator::BangEqual => Ok(Object::Boolean(!equals(token, &left, &right)?)),
+        _ => type_error(),
+    }
+}
+
+/// Integer division rounding towards negative infinity, so that it pairs with
+/// `floor_mod`. `None` on overflow.
+fn equals(token: &Token, left: &Object, right: &Object) -> Result<bool> {
+    left.equals(right, &mut Vec::new()).ok_or_else(|| {
+        InterpreterError::new(token, "Values are nested too deeply to compare.").into()
+    })
+}
+
+/// `to_string`, failing on collections nested too deeply to print.
+fn stringify(token: &Token, value: &Object) -> Result<String> {
+    match value {
+        Object::List(_) | Object::Map(_) => value.repr_within(&mut Vec::new()).ok_or_else(|| {
+            InterpreterError::new(token, "Value is nested too deeply to print.").into()
+        }),
+        _ => Ok(value.to_string()),
+    }
+}
+
+fn floor_div(l: i64, r: i64) -> Option<i64> {
+    let quotient = l.checked_div(r)?;
+    if l % r != 0 && (l < 0) != (r < 0) {
+        Some(quotient - 1)
+    } else {
+        Some(quotient)
+    }
+}
+
+/// The remainder of a floored division: it takes the sign of the divisor, so
+/// `-7 % 3` is `2` and `7 % -3` is `-2`.
+fn floor_mod(l: i64, r: i64) -> i64 {
+    let remainder = l.wrapping_rem(r);
+    if remainder != 0 && (remainder < 0) != (r < 0) {
+        remainder + r
+    } else {
+        remainder
+    }
+}
+
+fn floor_mod_float(l: f64, r: f64) -> f64 {
+    let remainder = l % r;
+    if remainder != 0.0 && (remainder < 0.0) != (r < 0.0) {
+        remainder + r
+    } else {
+        remainder
+    }
+}
+
+fn get_index(bracket: &Token, object: &Object, index: &Object) -> Result<Object> {
+    match object {
+        Object::List(list) => {
+            let list = list.borrow();
+            Ok(list[list_index(bracket, index, list.len())?].clone())
+        }
+        Object::Map(map) => match map.borrow().get(&map_key(bracket, index)?) {
+            Some(value) => Ok(value.clone()),
+            None => Err(InterpreterError::new(
+                bracket,
+                &format!("Key {} not found in map.", index.repr()),
+            )
+            .into()),
+        },
+        other => Err(InterpreterError::new(
+            bracket,
+            &format!("Can't index into {}.", other.type_name()),
+        )
+        .into()),
+    }
+}
+
+fn set_index(bracket: &Token, object: &Object, index: &Object, value: Object) -> Result<()> {
+    match object {
+        Object::List(list) => {
+            let mut list = list.borrow_mut();
+            let index = list_index(bracket, index, list.len())?;
+            list[index] = value;
+            Ok(())
+        }
+        Object::Map(map) => {
+            let key = map_key(bracket, index)?;
+            map.borrow_mut().insert(key, value);
+            Ok(())
+        }
+        other => Err(InterpreterError::new(
+            bracket,
+            &format!("Can't assign to an index of {}.", other.type_name()),
+        )
+        .into()),
+    }
+}
+
+/// Resolves `index` against a list of length `len`, counting negative indices
+/// back from the end.
+fn list_index(bracket: &Token, index: &Object, len: usize) -> Result<usize> {
+    let index = match index {
+        Object::Int(i) => *i,
+        other => {
+            return Err(InterpreterError::new(
+                bracket,
+                &format!("List index must be an int, got {}.", other.type_name()),
+            )
+            .into())
+        }
+    };
+    let resolved = if index < 0 { index + len as i64 } else { index };
+    if resolved < 0 || resolved >= len as i64 {
+        return Err(InterpreterError::new(
+            bracket,
+            &format!("Index {} out of bounds for list of length {}.", index, len),
+        )
+        .into());
+    }
+    Ok(resolved as usize)
+}
+
+fn map_key(token: &Token, key: &Object) -> Result<MapKey> {
+    MapKey::from_object(key).ok_or_else(|| {
+        InterpreterError::new(
+            token,
+            &format!(
+                "Map keys must be strings, ints or booleans, got {}.",
+                key.type_name()
+            ),
+        )
+        .into()
+    })
+}
+
+/// How many calls may be in progress at once before a call fails with a
+/// "Stack overflow." error instead of overflowing the native stack.
+pub const MAX_CALL_DEPTH: usize = 1000;
+
+/// How deeply lists and maps may nest inside one another and still be
+/// printed or compared.
+pub const MAX_NESTING_DEPTH: usize = 10_000;
+
+/// A native stack size that fits `MAX_CALL_DEPTH` nested calls with room to
+/// spare, even in debug builds. The main thread's default is too small.
+pub const STACK_SIZE: usize = 256 * 1024 * 1024;
+
 pub struct Interpreter {
-    environment: environment::Environment,
+    environment: Rc<RefCell<environment::Environment>>,
+    /// The number of function, method and class calls in progress.
+    call_depth: usize,
+    /// The file whose code is running, used to resolve relative imports.
+    current_file: Option<PathBuf>,
+    /// Directories searched for imports not found next to the importing file.
+    module_paths: Vec<PathBuf>,
+    /// Modules that finished running, by canonical path.
+    modules: HashMap<PathBuf, Rc<Module>>,
+    /// Files currently being run, outermost first, to detect import cycles.
+    loading: Vec<PathBuf>,
+    /// The `Error` class shared by every module, which caught runtime errors
+    /// are instances of.
+    error_class: Rc<Class>,
 }
-struct InterpreterError {
+pub struct InterpreterError {
     message: String,
     token: Token,
 }
 
+impl InterpreterError {
+    pub fn new(token: &Token, message: &str) -> Self {
+        Self {
+            message: message.to_string(),
+            token: token.clone(),
+        }
+    }
+}
+
+/// Anything that stops the normal statement-by-statement flow of execution.
+pub enum Unwind {
+    Error(InterpreterError),
+    /// A value raised by `throw`, with the `throw` keyword for error reporting.
+    Throw(Object, Token),
+    Return(Object),
+    Break(Option<String>),
+    Continue(Option<String>),
+}
+
+impl From<InterpreterError> for Unwind {
+    fn from(err: InterpreterError) -> Self {
+        Unwind::Error(err)
+    }
+}
+
 impl Interpreter {
     pub fn new() -> Self {
+        let error_class = natives::error_class();
+        let mut globals = environment::Environment::new();
+        natives::define(&mut globals, &error_class);
         Self {
-            environment: environment::Environment::new(),
+            environment: Rc::new(RefCell::new(globals)),
+            call_depth: 0,
+            current_file: None,
+            module_paths: module::search_paths(),
+            modules: HashMap::new(),
+            loading: Vec::new(),
+            error_class,
         }
     }
-    pub fn interpret(&mut self, stmts: &Vec<Stmt>) {
+
+    /// Records the script being run so its imports resolve relative to it.
+    pub fn set_main_file(&mut self, path: &Path) {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.loading = vec![path.clone()];
+        self.current_file = Some(path);
+    }
+
+    /// Makes `file` the one relative imports resolve against, returning the
+    /// previous one so that it can be restored.
+    pub fn swap_current_file(&mut self, file: Option<PathBuf>) -> Option<PathBuf> {
+        std::mem::replace(&mut self.current_file, file)
+    }
+
+    /// Runs `stmts` until the first uncaught runtime error or thrown value,
+    /// which is reported and makes this return `false`.
+    pub fn interpret(&mut self, stmts: &[Stmt]) -> bool {
         for stmt in stmts {
             match self.execute(stmt) {
                 Ok(_) => {}
-                Err(err) => {
+                Err(Unwind::Error(err)) => {
                     log::log_message::print_code_error(err.token.line, &err.message);
+                    return false;
+                }
+                Err(Unwind::Throw(value, keyword)) => {
+                    let message = match &value {
+                        Object::Instance(instance) => instance.borrow().field("message"),
+                        _ => None,
+                    }
+                    .unwrap_or(value);
+                    log::log_message::print_code_error(
+                        keyword.line,
+                        &format!("Uncaught exception: {}", message),
+                    );
+                    return false;
                 }
+                Err(Unwind::Return(_) | Unwind::Break(_) | Unwind::Continue(_)) => {}
             }
         }
+        true
     }
 
     fn evaluate(&mut self, expr: &Expr) -> Result<Object> {
@@ -80,54 +679,190 @@ impl Interpreter {
         stmt.accept(self)
     }
 
-    fn execute_block(&mut self, stmts: &Vec<Stmt>, env: environment::Environment) {
-        let previous = self.environment.clone();
-        self.environment = env;
-        for stmt in stmts {
-            match self.execute(stmt) {
-                Ok(_) => {}
-                Err(err) => {
-                    log::log_message::print_code_error(err.token.line, &err.message);
+    pub fn execute_block(
+        &mut self,
+        stmts: &[Stmt],
+        env: Rc<RefCell<environment::Environment>>,
+    ) -> Result<()> {
+        let previous = std::mem::replace(&mut self.environment, env);
+        let result = stmts.iter().try_for_each(|stmt| self.execute(stmt));
+        self.environment = previous;
+        result
+    }
+
+    fn evaluate_in(
+        &mut self,
+        expr: &Expr,
+        env: Rc<RefCell<environment::Environment>>,
+    ) -> Result<Object> {
+        let previous = std::mem::replace(&mut self.environment, env);
+        let result = self.evaluate(expr);
+        self.environment = previous;
+        result
+    }
+
+    /// Returns the module `path` refers to, running it first unless it has
+    /// already been imported.
+    fn import(&mut self, keyword: &Token, path: &str) -> Result<Rc<Module>> {
+        let base = self
+            .current_file
+            .as_ref()
+            .and_then(|file| file.parent())
+            .map_or_else(|| PathBuf::from("."), Path::to_path_buf);
+        let resolved = module::resolve(path, &base, &self.module_paths).map_err(|searched| {
+            let searched = searched
+                .iter()
+                .map(|candidate| candidate.display().to_string())
+                .collect::<Vec<String>>()
+                .join(", ");
+            InterpreterError::new(
+                keyword,
+                &format!("Module \"{}\" not found (searched {}).", path, searched),
+            )
+        })?;
+        if let Some(module) = self.modules.get(&resolved) {
+            return Ok(module.clone());
+        }
+        if let Some(start) = self.loading.iter().position(|file| *file == resolved) {
+            let cycle = self.loading[start..]
+                .iter()
+                .chain(std::iter::once(&resolved))
+                .map(|file| file.display().to_string())
+                .collect::<Vec<String>>()
+                .join(" -> ");
+            return Err(
+                InterpreterError::new(keyword, &format!("Import cycle: {}.", cycle)).into(),
+            );
+        }
+
+        let source = fs::read_to_string(&resolved).map_err(|err| {
+            InterpreterError::new(
+                keyword,
+                &format!("Can't read module \"{}\": {}.", resolved.display(), err),
+            )
+        })?;
+        let mut scanner = Scanner::new(&source);
+        scanner.set_module(path);
+        let tokens = scanner.scan_tokens().clone();
+        let mut parser = Parser::new(tokens);
+        parser.set_module(path);
+        let stmts = parser.parse();
+        if scanner.had_error() || parser.had_error() {
+            return Err(InterpreterError::new(
+                keyword,
+                &format!("Module \"{}\" has errors.", path),
+            )
+            .into());
+        }
+
+        let mut globals = environment::Environment::new();
+        natives::define(&mut globals, &self.error_class);
+        let globals = Rc::new(RefCell::new(globals));
+        self.loading.push(resolved.clone());
+        let importer = self.current_file.replace(resolved.clone());
+        let result = self.execute_block(&stmts, globals.clone());
+        self.current_file = importer;
+        self.loading.pop();
+        // Report what escapes the module at the import, so that the line
+        // number printed belongs to the importing file.
+        result.map_err(|unwind| match unwind {
+            Unwind::Error(err) => InterpreterError::new(
+                keyword,
+                &format!(
+                    "In module \"{}\", line {}: {}",
+                    path, err.token.line, err.message
+                ),
+            )
+            .into(),
+            Unwind::Throw(value, _) => Unwind::Throw(value, keyword.clone()),
+            unwind => unwind,
+        })?;
+
+        let module = Rc::new(Module::new(
+            resolved.clone(),
+            globals,
+            module::exports(&stmts),
+        ));
+        self.modules.insert(resolved, module.clone());
+        Ok(module)
+    }
+
+    fn execute_catch(&mut self, name: &Token, handler: &[Stmt], caught: Object) -> Result<()> {
+        let mut env = environment::Environment::new_enclosed(self.environment.clone());
+        env.define(name.lexeme.clone(), caught);
+        self.execute_block(handler, Rc::new(RefCell::new(env)))
+    }
+
+    /// Reads the current value of an assignable `target`, stores the value
+    /// `compute` derives from it and returns both. Any object or index
+    /// expression inside `target` is evaluated exactly once.
+    fn update(
+        &mut self,
+        target: &Expr,
+        compute: impl FnOnce(&mut Self, Object) -> Result<Object>,
+    ) -> Result<(Object, Object)> {
+        match target {
+            Expr::Variable { name } => {
+                let old = self.environment.borrow().get(name)?;
+                let new = compute(self, old.clone())?;
+                self.environment.borrow_mut().assign(name, new.clone())?;
+                Ok((old, new))
+            }
+            Expr::Get { object, name } => match &self.evaluate(object)? {
+                Object::Instance(instance) => {
+                    let old = Instance::get(instance, name)?;
+                    let new = compute(self, old.clone())?;
+                    instance.borrow_mut().set(name, new.clone());
+                    Ok((old, new))
                 }
+                _ => Err(InterpreterError::new(name, "Only instances have fields.").into()),
+            },
+            Expr::Index {
+                object,
+                bracket,
+                index,
+            } => {
+                let object = self.evaluate(object)?;
+                let index = self.evaluate(index)?;
+                let old = get_index(bracket, &object, &index)?;
+                let new = compute(self, old.clone())?;
+                set_index(bracket, &object, &index, new.clone())?;
+                Ok((old, new))
             }
+            _ => unreachable!("the parser only allows variables, properties and indices"),
         }
-        /*
-         * TODO: the environment reveresed is not correct since the update inside the block is lost
-         */
-        self.environment = previous;
     }
 }
 
-type Result<T> = std::result::Result<T, InterpreterError>;
+pub type Result<T> = std::result::Result<T, Unwind>;
 
 impl stmt::Visitor<Result<()>> for Interpreter {
     fn visit_expr(&mut self, expr: &Expr) -> Result<()> {
         self.evaluate(expr)?;
         Ok(())
     }
-    fn visit_print(&mut self, expr: &Expr) -> Result<()> {
+    fn visit_print(&mut self, keyword: &Token, expr: &Expr) -> Result<()> {
         let value = self.evaluate(expr)?;
-        println!("{}", value);
+        println!("{}", stringify(keyword, &value)?);
         Ok(())
     }
 
+    fn visit_const(&mut self, name: &Token, initializer: &Expr) -> Result<()> {
+        let value = self.evaluate(initializer)?;
+        self.environment.borrow_mut().declare(name, value, true)
+    }
+
     fn visit_var(&mut self, name: &Token, initializer: &Option<Expr>) -> Result<()> {
-        match initializer {
-            Some(expr) => {
-                let value = self.evaluate(expr)?;
-                self.environment.define(name.lexeme.clone(), value);
-            }
-            None => self.environment.define(name.lexeme.clone(), Object::Nil),
-        }
-        Ok(())
+        let value = match initializer {
+            Some(expr) => self.evaluate(expr)?,
+            None => Object::Nil,
+        };
+        self.environment.borrow_mut().declare(name, value, false)
     }
 
     fn visit_block(&mut self, stmts: &Vec<Stmt>) -> Result<()> {
-        self.execute_block(
-            stmts,
-            environment::Environment::new_enclosed(self.environment.clone()),
-        );
-        Ok(())
+        let env = environment::Environment::new_enclosed(self.environment.clone());
+        self.execute_block(stmts, Rc::new(RefCell::new(env)))
     }
 
     fn visit_if(
@@ -144,39 +879,216 @@ impl stmt::Visitor<Result<()>> for Interpreter {
         Ok(())
     }
 
-    fn visit_while(&mut self, condition: &Expr, body: &Stmt) -> Result<()> {
+    fn visit_while(
+        &mut self,
+        condition: &Expr,
+        body: &Stmt,
+        increment: &Option<Expr>,
+        label: &Option<Token>,
+    ) -> Result<()> {
+        let targets = |target: &Option<String>| match target {
+            Some(target) => label.as_ref().is_some_and(|l| &l.lexeme == target),
+            None => true,
+        };
         while self.evaluate(condition)?.is_truthy() {
-            self.execute(body)?;
+            match self.execute(body) {
+                Ok(_) => {}
+                Err(Unwind::Break(target)) if targets(&target) => break,
+                Err(Unwind::Continue(target)) if targets(&target) => {}
+                Err(err) => return Err(err),
+            }
+            if let Some(increment) = increment {
+                self.evaluate(increment)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_break(&mut self, _keyword: &Token, label: &Option<Token>) -> Result<()> {
+        Err(Unwind::Break(label.as_ref().map(|l| l.lexeme.clone())))
+    }
+
+    fn visit_continue(&mut self, _keyword: &Token, label: &Option<Token>) -> Result<()> {
+        Err(Unwind::Continue(label.as_ref().map(|l| l.lexeme.clone())))
+    }
+
+    fn visit_function(&mut self, name: &Token, params: &[Token], body: &[Stmt]) -> Result<()> {
+        let function = Function::new(
+            Some(name),
+            params,
+            body,
+            self.environment.clone(),
+            false,
+            self.current_file.clone(),
+        );
+        self.environment
+            .borrow_mut()
+            .declare(name, Object::Function(Rc::new(function)), false)
+    }
+
+    fn visit_return(&mut self, _keyword: &Token, value: &Option<Expr>) -> Result<()> {
+        let value = match value {
+            Some(expr) => self.evaluate(expr)?,
+            None => Object::Nil,
+        };
+        Err(Unwind::Return(value))
+    }
+
+    fn visit_class(
+        &mut self,
+        name: &Token,
+        superclass: &Option<Expr>,
+        methods: &[Stmt],
+    ) -> Result<()> {
+        let superclass = match superclass {
+            Some(expr @ Expr::Variable { name: superclass }) => {
+                if superclass.lexeme == name.lexeme {
+                    return Err(InterpreterError::new(
+                        superclass,
+                        "A class can't inherit from itself.",
+                    )
+                    .into());
+                }
+                match &self.evaluate(expr)? {
+                    Object::Class(class) => Some(class.clone()),
+                    _ => {
+                        return Err(InterpreterError::new(
+                            superclass,
+                            "Superclass must be a class.",
+                        )
+                        .into())
+                    }
+                }
+            }
+            Some(_) => unreachable!("the parser only produces variable superclasses"),
+            None => None,
+        };
+
+        let environment = match &superclass {
+            Some(superclass) => {
+                let mut environment =
+                    environment::Environment::new_enclosed(self.environment.clone());
+                environment.define("super".to_string(), Object::Class(superclass.clone()));
+                Rc::new(RefCell::new(environment))
+            }
+            None => self.environment.clone(),
+        };
+
+        let mut class_methods = HashMap::new();
+        for method in methods {
+            if let Stmt::Function { name, params, body } = method {
+                let function = Function::new(
+                    Some(name),
+                    params,
+                    body,
+                    environment.clone(),
+                    name.lexeme == "init",
+                    self.current_file.clone(),
+                );
+                class_methods.insert(name.lexeme.clone(), Rc::new(function));
+            }
+        }
+        let class = Class::new(&name.lexeme, superclass, class_methods);
+        self.environment
+            .borrow_mut()
+            .declare(name, Object::Class(Rc::new(class)), false)
+    }
+
+    fn visit_import(
+        &mut self,
+        keyword: &Token,
+        path: &str,
+        alias: &Option<Token>,
+        names: &[(Token, Token)],
+    ) -> Result<()> {
+        let module = self.import(keyword, path)?;
+        for (name, local) in names {
+            let value = module.get(name)?;
+            self.environment
+                .borrow_mut()
+                .define(local.lexeme.clone(), value);
+        }
+        if let Some(alias) = alias {
+            self.environment
+                .borrow_mut()
+                .define(alias.lexeme.clone(), Object::Module(module));
         }
         Ok(())
     }
+
+    fn visit_export(&mut self, declaration: &Stmt) -> Result<()> {
+        self.execute(declaration)
+    }
+
+    /// An `Error` thrown without a `line` field is given the line it was
+    /// thrown from.
+    fn visit_throw(&mut self, keyword: &Token, value: &Expr) -> Result<()> {
+        let value = self.evaluate(value)?;
+        if let Object::Instance(instance) = &value {
+            let mut instance = instance.borrow_mut();
+            if instance.is_error() && instance.field("line").is_none() {
+                let line = Token::new(TokenType::IDENTIFIER, "line".to_string(), None, 0);
+                instance.set(&line, Object::Int(i64::from(keyword.line)));
+            }
+        }
+        Err(Unwind::Throw(value, keyword.clone()))
+    }
+
+    /// Runtime errors are caught as `Error` objects; thrown values are caught
+    /// as they are. `break`, `continue` and `return` pass through the handler,
+    /// but every way out of the statement runs the `finally` block, whose own
+    /// unwinding replaces whatever was in progress.
+    fn visit_try(
+        &mut self,
+        body: &[Stmt],
+        catch: &Option<(Token, Vec<Stmt>)>,
+        finally: &Option<Vec<Stmt>>,
+    ) -> Result<()> {
+        let env = environment::Environment::new_enclosed(self.environment.clone());
+        let result = match (self.execute_block(body, Rc::new(RefCell::new(env))), catch) {
+            (Err(Unwind::Error(err)), Some((name, handler))) => {
+                let caught = natives::error_object(&self.error_class, &err.message, err.token.line);
+                self.execute_catch(name, handler, caught)
+            }
+            (Err(Unwind::Throw(value, _)), Some((name, handler))) => {
+                self.execute_catch(name, handler, value)
+            }
+            (result, _) => result,
+        };
+        if let Some(finally) = finally {
+            let env = environment::Environment::new_enclosed(self.environment.clone());
+            self.execute_block(finally, Rc::new(RefCell::new(env)))?;
+        }
+        result
+    }
 }
 
 impl Visitor<Result<Object>> for Interpreter {
     fn visit_literal(&mut self, expr: &Literal) -> Result<Object> {
-        match expr {
-            Literal::String(s) => Ok(Object::String(s.clone())),
-            Literal::Int(i) => Ok(Object::Int(*i)),
-            Literal::Boolean(b) => Ok(Object::Boolean(*b)),
-            Literal::Float(fl) => Ok(Object::Float(*fl)),
-            Literal::Nil => Ok(Object::Nil),
-            Literal::Identifier(i) => Ok(Object::Identifier(i.clone())),
-        }
+        Ok(expr.into())
     }
 
-    fn visit_unary(&mut self, operator: &Operator, right: &Expr) -> Result<Object> {
-        let right = right.accept(self);
-        match operator {
-            Operator::Bang => match right? {
-                Object::Boolean(b) => Ok(Object::Boolean(!b)),
-                _ => Ok(Object::Nil),
-            },
-            Operator::Minus => match right? {
-                Object::Int(i) => Ok(Object::Int(-i)),
-                Object::Float(fl) => Ok(Object::Float(-fl)),
-                _ => Ok(Object::Nil),
-            },
-            _ => Ok(Object::Nil),
+    fn visit_unary(&mut self, operator: &Operator, token: &Token, right: &Expr) -> Result<Object> {
+        let right = right.accept(self)?;
+        match (operator, &right) {
+            (Operator::Bang, Object::Boolean(b)) => Ok(Object::Boolean(!b)),
+            (Operator::Minus, Object::Int(i)) => {
+                i.checked_neg().map(Object::Int).ok_or_else(|| {
+                    InterpreterError::new(token, &format!("Integer overflow in '{}'.", operator))
+                        .into()
+                })
+            }
+            (Operator::Minus, Object::Float(fl)) => Ok(Object::Float(-fl)),
+            (Operator::Tilde, Object::Int(i)) => Ok(Object::Int(!i)),
+            _ => Err(InterpreterError::new(
+                token,
+                &format!(
+                    "Unsupported operand type for '{}': {}.",
+                    operator,
+                    right.type_name()
+                ),
+            )
+            .into()),
         }
     }
 
@@ -184,62 +1096,25 @@ impl Visitor<Result<Object>> for Interpreter {
         expression.accept(self)
     }
 
-    fn visit_binary(&mut self, left: &Expr, operator: &Operator, right: &Expr) -> Result<Object> {
+    fn visit_binary(
+        &mut self,
+        left: &Expr,
+        operator: &Operator,
+        token: &Token,
+        right: &Expr,
+    ) -> Result<Object> {
         let left = left.accept(self)?;
         let right = right.accept(self)?;
-        match operator {
-            Operator::Plus => match (left, right) {
-                (Object::Int(l), Object::Int(r)) => Ok(Object::Int(l + r)),
-                (Object::Float(l), Object::Float(r)) => Ok(Object::Float(l + r)),
-                (Object::String(l), Object::String(r)) => Ok(Object::String(format!("{}{}", l, r))),
-                _ => Ok(Object::Nil),
-            },
-            Operator::Minus => match (left, right) {
-                (Object::Int(l), Object::Int(r)) => Ok(Object::Int(l - r)),
-                (Object::Float(l), Object::Float(r)) => Ok(Object::Float(l - r)),
-                _ => Ok(Object::Nil),
-            },
-            Operator::Slash => match (left, right) {
-                (Object::Int(l), Object::Int(r)) => Ok(Object::Int(l / r)),
-                (Object::Float(l), Object::Float(r)) => Ok(Object::Float(l / r)),
-                _ => Ok(Object::Nil),
-            },
-            Operator::Star => match (left, right) {
-                (Object::Int(l), Object::Int(r)) => Ok(Object::Int(l * r)),
-                (Object::Float(l), Object::Float(r)) => Ok(Object::Float(l * r)),
-                _ => Ok(Object::Nil),
-            },
-            Operator::Greater => match (left, right) {
-                (Object::Int(l), Object::Int(r)) => Ok(Object::Boolean(l > r)),
-                (Object::Float(l), Object::Float(r)) => Ok(Object::Boolean(l > r)),
-                _ => Ok(Object::Nil),
-            },
-            Operator::GreaterEqual => match (left, right) {
-                (Object::Int(l), Object::Int(r)) => Ok(Object::Boolean(l >= r)),
-                (Object::Float(l), Object::Float(r)) => Ok(Object::Boolean(l >= r)),
-                _ => Ok(Object::Nil),
-            },
-            Operator::Less => match (left, right) {
-                (Object::Int(l), Object::Int(r)) => Ok(Object::Boolean(l < r)),
-                (Object::Float(l), Object::Float(r)) => Ok(Object::Boolean(l < r)),
-                _ => Ok(Object::Nil),
-            },
-            Operator::LessEqual => match (left, right) {
-                (Object::Int(l), Object::Int(r)) => Ok(Object::Boolean(l <= r)),
-                (Object::Float(l), Object::Float(r)) => Ok(Object::Boolean(l <= r)),
-                _ => Ok(Object::Nil),
-            },
-            _ => Ok(Object::Nil),
-        }
+        binary_operation(operator, token, left, right)
     }
 
     fn visit_variable(&mut self, name: &Token) -> Result<Object> {
-        Ok(self.environment.get(name))
+        self.environment.borrow().get(name)
     }
 
     fn visit_assignment(&mut self, name: &Token, value: &Expr) -> Result<Object> {
         let value = value.accept(self)?;
-        self.environment.assign(name, value.clone());
+        self.environment.borrow_mut().assign(name, value.clone())?;
         Ok(value)
     }
 
@@ -260,7 +1135,1411 @@ impl Visitor<Result<Object>> for Interpreter {
                     right.accept(self)
                 }
             }
+            Operator::Coalesce => match left {
+                Object::Nil => right.accept(self),
+                _ => Ok(left),
+            },
             _ => Ok(Object::Nil),
         }
     }
+
+    fn visit_conditional(
+        &mut self,
+        condition: &Expr,
+        then_branch: &Expr,
+        else_branch: &Expr,
+    ) -> Result<Object> {
+        if self.evaluate(condition)?.is_truthy() {
+            self.evaluate(then_branch)
+        } else {
+            self.evaluate(else_branch)
+        }
+    }
+
+    fn visit_call(&mut self, callee: &Expr, paren: &Token, arguments: &[Expr]) -> Result<Object> {
+        let callee = self.evaluate(callee)?;
+        let arguments = arguments
+            .iter()
+            .map(|arg| self.evaluate(arg))
+            .collect::<Result<Vec<Object>>>()?;
+
+        let arity = match &callee {
+            Object::Function(function) => function.arity(),
+            Object::NativeFunction(function) => function.arity(),
+            Object::Class(class) => class.arity(),
+            _ => {
+                return Err(
+                    InterpreterError::new(paren, "Can only call functions and classes.").into(),
+                )
+            }
+        };
+        if arguments.len() != arity {
+            return Err(InterpreterError::new(
+                paren,
+                &format!("Expected {} arguments but got {}.", arity, arguments.len()),
+            )
+            .into());
+        }
+
+        if self.call_depth == MAX_CALL_DEPTH {
+            return Err(InterpreterError::new(paren, "Stack overflow.").into());
+        }
+        self.call_depth += 1;
+        let result = match &callee {
+            Object::Function(function) => function.call(self, arguments),
+            Object::NativeFunction(function) => function.call(paren, arguments),
+            Object::Class(class) => Class::call(class, self, arguments),
+            _ => unreachable!(),
+        };
+        self.call_depth -= 1;
+        result
+    }
+
+    fn visit_get(&mut self, object: &Expr, name: &Token) -> Result<Object> {
+        match &self.evaluate(object)? {
+            Object::Instance(instance) => Instance::get(instance, name),
+            Object::Module(module) => module.get(name),
+            _ => Err(InterpreterError::new(name, "Only instances have properties.").into()),
+        }
+    }
+
+    fn visit_set(&mut self, object: &Expr, name: &Token, value: &Expr) -> Result<Object> {
+        match &self.evaluate(object)? {
+            Object::Instance(instance) => {
+                let value = self.evaluate(value)?;
+                instance.borrow_mut().set(name, value.clone());
+                Ok(value)
+            }
+            _ => Err(InterpreterError::new(name, "Only instances have fields.").into()),
+        }
+    }
+
+    fn visit_this(&mut self, keyword: &Token) -> Result<Object> {
+        self.environment.borrow().get(keyword)
+    }
+
+    fn visit_lambda(
+        &mut self,
+        _keyword: &Token,
+        params: &[Token],
+        body: &[Stmt],
+    ) -> Result<Object> {
+        let function = Function::new(
+            None,
+            params,
+            body,
+            self.environment.clone(),
+            false,
+            self.current_file.clone(),
+        );
+        Ok(Object::Function(Rc::new(function)))
+    }
+
+    fn visit_interpolation(&mut self, start: &Token, parts: &[Expr]) -> Result<Object> {
+        let mut string = String::new();
+        for part in parts {
+            let value = self.evaluate(part)?;
+            string.push_str(&stringify(start, &value)?);
+        }
+        Ok(Object::String(string))
+    }
+
+    fn visit_list(&mut self, elements: &[Expr]) -> Result<Object> {
+        let elements = elements
+            .iter()
+            .map(|e| self.evaluate(e))
+            .collect::<Result<Vec<Object>>>()?;
+        Ok(Object::List(Rc::new(RefCell::new(elements))))
+    }
+
+    fn visit_map(&mut self, brace: &Token, entries: &[(Expr, Expr)]) -> Result<Object> {
+        let mut map = Map::new();
+        for (key, value) in entries {
+            let key = self.evaluate(key)?;
+            let value = self.evaluate(value)?;
+            map.insert(map_key(brace, &key)?, value);
+        }
+        Ok(Object::Map(Rc::new(RefCell::new(map))))
+    }
+
+    fn visit_index(&mut self, object: &Expr, bracket: &Token, index: &Expr) -> Result<Object> {
+        let object = self.evaluate(object)?;
+        let index = self.evaluate(index)?;
+        get_index(bracket, &object, &index)
+    }
+
+    fn visit_index_set(
+        &mut self,
+        object: &Expr,
+        bracket: &Token,
+        index: &Expr,
+        value: &Expr,
+    ) -> Result<Object> {
+        let object = self.evaluate(object)?;
+        let index = self.evaluate(index)?;
+        let value = self.evaluate(value)?;
+        set_index(bracket, &object, &index, value.clone())?;
+        Ok(value)
+    }
+
+    fn visit_compound_assignment(
+        &mut self,
+        target: &Expr,
+        operator: &Operator,
+        token: &Token,
+        value: &Expr,
+    ) -> Result<Object> {
+        let (_, new) = self.update(target, |interpreter, old| {
+            let value = interpreter.evaluate(value)?;
+            binary_operation(operator, token, old, value)
+        })?;
+        Ok(new)
+    }
+
+    fn visit_increment(
+        &mut self,
+        target: &Expr,
+        operator: &Operator,
+        token: &Token,
+        prefix: bool,
+    ) -> Result<Object> {
+        // Errors name the `++` or `--` as written, not the `+ 1` it stands for.
+        let step = if *operator == Operator::Plus { 1 } else { -1 };
+        let (old, new) = self.update(target, |_, old| match old {
+            Object::Int(i) => i.checked_add(step).map(Object::Int).ok_or_else(|| {
+                InterpreterError::new(token, &format!("Integer overflow in '{}'.", token.lexeme))
+                    .into()
+            }),
+            Object::Float(fl) => Ok(Object::Float(fl + step as f64)),
+            _ => Err(InterpreterError::new(
+                token,
+                &format!(
+                    "Unsupported operand type for '{}': {}.",
+                    token.lexeme,
+                    old.type_name()
+                ),
+            )
+            .into()),
+        })?;
+        Ok(if prefix { new } else { old })
+    }
+
+    fn visit_match(
+        &mut self,
+        keyword: &Token,
+        subject: &Expr,
+        arms: &[MatchArm],
+    ) -> Result<Object> {
+        let value = self.evaluate(subject)?;
+        for arm in arms {
+            let mut bindings = Vec::new();
+            if !arm.pattern.matches(&value, &mut bindings) {
+                continue;
+            }
+            let mut env = environment::Environment::new_enclosed(self.environment.clone());
+            for (name, bound) in bindings {
+                env.define(name.lexeme, bound);
+            }
+            let env = Rc::new(RefCell::new(env));
+            if let Some(guard) = &arm.guard {
+                if !self.evaluate_in(guard, env.clone())?.is_truthy() {
+                    continue;
+                }
+            }
+            return match &arm.body {
+                ArmBody::Expr(body) => self.evaluate_in(body, env),
+                ArmBody::Block(body) => {
+                    self.execute_block(body, env)?;
+                    Ok(Object::Nil)
+                }
+            };
+        }
+        Err(InterpreterError::new(
+            keyword,
+            &format!("No match arm for value {}.", value.repr()),
+        )
+        .into())
+    }
+
+    fn visit_super(&mut self, keyword: &Token, method: &Token) -> Result<Object> {
+        let superclass = match &self.environment.borrow().get(keyword)? {
+            Object::Class(superclass) => superclass.clone(),
+            _ => unreachable!("'super' is only bound to classes"),
+        };
+        let this = Token::new(TokenType::THIS, "this".to_string(), None, keyword.line);
+        let object = self.environment.borrow().get(&this)?;
+        match superclass.find_method(&method.lexeme) {
+            Some(found) => Ok(Object::Function(Rc::new(found.bind(object)))),
+            None => Err(InterpreterError::new(
+                method,
+                &format!("Undefined property '{}'.", method.lexeme),
+            )
+            .into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::scanner::Scanner;
+    use crate::parser::parser::Parser;
+
+    fn run(source: &str) -> Interpreter {
+        let tokens = Scanner::new(source).scan_tokens().clone();
+        let stmts = Parser::new(tokens).parse();
+        let mut interpreter = Interpreter::new();
+        interpreter.interpret(&stmts);
+        interpreter
+    }
+
+    fn run_error(source: &str) -> String {
+        let tokens = Scanner::new(source).scan_tokens().clone();
+        let stmts = Parser::new(tokens).parse();
+        let mut interpreter = Interpreter::new();
+        for stmt in &stmts {
+            if let Err(Unwind::Error(err)) = interpreter.execute(stmt) {
+                return format!("{}: {}", err.token.line, err.message);
+            }
+        }
+        panic!("expected a runtime error");
+    }
+
+    fn global(interpreter: &Interpreter, name: &str) -> String {
+        let name = Token::new(TokenType::IDENTIFIER, name.to_string(), None, 0);
+        match interpreter.environment.borrow().get(&name) {
+            Ok(value) => value.to_string(),
+            Err(_) => panic!("global '{}' is not defined", name.lexeme),
+        }
+    }
+
+    fn is_defined(interpreter: &Interpreter, name: &str) -> bool {
+        let name = Token::new(TokenType::IDENTIFIER, name.to_string(), None, 0);
+        let defined = interpreter.environment.borrow().get(&name).is_ok();
+        defined
+    }
+
+    #[test]
+    fn test_block_assignment_persists() {
+        let interpreter = run("var a = 1; { a = 2; }");
+        assert_eq!(global(&interpreter, "a"), "2");
+    }
+
+    #[test]
+    fn test_nested_block_assignment_persists() {
+        let interpreter = run("var a = 1; var b = 1; { var b = 10; { a = a + b; b = 20; } }");
+        assert_eq!(global(&interpreter, "a"), "11");
+        assert_eq!(global(&interpreter, "b"), "1");
+    }
+
+    #[test]
+    fn test_while_body_assignment_persists() {
+        let interpreter =
+            run("var i = 0; var sum = 0; while (i < 5) { sum = sum + i; i = i + 1; }");
+        assert_eq!(global(&interpreter, "i"), "5");
+        assert_eq!(global(&interpreter, "sum"), "10");
+    }
+
+    #[test]
+    fn test_function_assignment_persists() {
+        let interpreter = run("var count = 0; fun bump() { count = count + 1; } bump(); bump();");
+        assert_eq!(global(&interpreter, "count"), "2");
+    }
+
+    #[test]
+    fn test_class_fields_and_methods() {
+        let interpreter = run("
+            class Point {
+                init(x, y) { this.x = x; this.y = y; }
+                sum() { return this.x + this.y; }
+            }
+            var p = Point(3, 4);
+            var before = p.sum();
+            var sum = p.sum;
+            p.x = 10;
+            var after = sum();
+        ");
+        assert_eq!(global(&interpreter, "p"), "<Point instance>");
+        assert_eq!(global(&interpreter, "Point"), "<class Point>");
+        assert_eq!(global(&interpreter, "before"), "7");
+        assert_eq!(global(&interpreter, "after"), "14");
+    }
+
+    #[test]
+    fn test_initializer_returns_instance() {
+        let interpreter = run("
+            class Counter { init() { this.n = 0; return; } }
+            var c = Counter();
+            var again = c.init();
+        ");
+        assert_eq!(global(&interpreter, "again"), "<Counter instance>");
+    }
+
+    #[test]
+    fn test_inherited_and_super_methods() {
+        let interpreter = run("
+            class A {
+                init(n) { this.n = n; }
+                name() { return \"A\"; }
+            }
+            class B < A {
+                name() { return \"B\" + super.name(); }
+            }
+            class C < B {
+                name() { return \"C\" + super.name(); }
+            }
+            var c = C(1);
+            var n = c.n;
+            var name = c.name();
+        ");
+        assert_eq!(global(&interpreter, "n"), "1");
+        assert_eq!(global(&interpreter, "name"), "CBA");
+    }
+
+    #[test]
+    fn test_invalid_superclass() {
+        let interpreter = run("var NotAClass = 1; class A < NotAClass {}");
+        assert!(!is_defined(&interpreter, "A"));
+        let interpreter = run("class B < B {}");
+        assert!(!is_defined(&interpreter, "B"));
+    }
+
+    #[test]
+    fn test_for_loop() {
+        let interpreter = run("
+            var sum = 0;
+            for (var i = 1; i <= 4; i = i + 1) sum = sum + i;
+            var j = 0;
+            for (; j < 3;) j = j + 1;
+        ");
+        assert_eq!(global(&interpreter, "sum"), "10");
+        assert_eq!(global(&interpreter, "j"), "3");
+        assert!(!is_defined(&interpreter, "i"));
+    }
+
+    #[test]
+    fn test_break_and_continue() {
+        let interpreter = run("
+            var sum = 0;
+            for (var i = 0; i < 10; i = i + 1) {
+                if (i > 2 and i < 4) continue;
+                if (i > 5) break;
+                sum = sum + i;
+            }
+            var n = 0;
+            while (true) { { n = n + 1; if (n > 4) break; } }
+        ");
+        assert_eq!(global(&interpreter, "sum"), "12");
+        assert_eq!(global(&interpreter, "n"), "5");
+    }
+
+    #[test]
+    fn test_labeled_break_and_continue() {
+        let interpreter = run("
+            var pairs = 0;
+            outer: for (var i = 0; i < 4; i = i + 1) {
+                for (var j = 0; j < 4; j = j + 1) {
+                    if (j > i) continue outer;
+                    if (i > 2) break outer;
+                    pairs = pairs + 1;
+                }
+            }
+        ");
+        assert_eq!(global(&interpreter, "pairs"), "6");
+    }
+
+    #[test]
+    fn test_type_errors() {
+        assert_eq!(
+            run_error("\"a\" - 1;"),
+            "1: Unsupported operand types for '-': string and int."
+        );
+        assert_eq!(
+            run_error("var x = 1;\nvar y = x + \"s\";"),
+            "2: Unsupported operand types for '+': int and string."
+        );
+        assert_eq!(
+            run_error("-\"x\";"),
+            "1: Unsupported operand type for '-': string."
+        );
+        assert_eq!(
+            run_error("!5;"),
+            "1: Unsupported operand type for '!': int."
+        );
+        assert_eq!(
+            run_error("1 < true;"),
+            "1: Unsupported operand types for '<': int and boolean."
+        );
+    }
+
+    #[test]
+    fn test_runtime_error_stops_execution() {
+        let tokens = Scanner::new("var a = 1; a = -nil; a = 3;")
+            .scan_tokens()
+            .clone();
+        let stmts = Parser::new(tokens).parse();
+        let mut interpreter = Interpreter::new();
+        assert!(!interpreter.interpret(&stmts));
+        assert_eq!(global(&interpreter, "a"), "1");
+    }
+
+    #[test]
+    fn test_equality() {
+        let interpreter = run("
+            var ints = 1 == 1;
+            var floats = 2.5 == 2.5;
+            var strings = \"a\" == \"a\";
+            fun nothing() {}
+            var nils = nothing() == nothing();
+            var nilVsFalse = nothing() == false;
+            var bools = true != false;
+            var mixedTypes = 1 == \"1\";
+        ");
+        for name in ["ints", "floats", "strings", "nils", "bools"] {
+            assert_eq!(global(&interpreter, name), "true", "{}", name);
+        }
+        assert_eq!(global(&interpreter, "mixedTypes"), "false");
+        assert_eq!(global(&interpreter, "nilVsFalse"), "false");
+    }
+
+    #[test]
+    fn test_int_float_equality() {
+        let interpreter = run("
+            var same = 1 == 1.0;
+            var reversed = 2.0 == 2;
+            var different = 1 == 1.5;
+            var notEqual = 3 != 3.0;
+        ");
+        assert_eq!(global(&interpreter, "same"), "true");
+        assert_eq!(global(&interpreter, "reversed"), "true");
+        assert_eq!(global(&interpreter, "different"), "false");
+        assert_eq!(global(&interpreter, "notEqual"), "false");
+    }
+
+    #[test]
+    fn test_reference_equality() {
+        let interpreter = run("
+            class A {}
+            fun f() {}
+            var a = A();
+            var sameInstance = a == a;
+            var otherInstance = a == A();
+            var sameClass = A == A;
+            var sameFunction = f == f;
+        ");
+        assert_eq!(global(&interpreter, "sameInstance"), "true");
+        assert_eq!(global(&interpreter, "otherInstance"), "false");
+        assert_eq!(global(&interpreter, "sameClass"), "true");
+        assert_eq!(global(&interpreter, "sameFunction"), "true");
+    }
+
+    #[test]
+    fn test_mixed_arithmetic_promotes_to_float() {
+        let interpreter = run("
+            var sum = 1 + 2.5;
+            var difference = 4.5 - 2;
+            var product = 2 * 1.5;
+            var less = 1 < 1.5;
+            var greater = 2.0 >= 3;
+        ");
+        assert_eq!(global(&interpreter, "sum"), "3.5");
+        assert_eq!(global(&interpreter, "difference"), "2.5");
+        assert_eq!(global(&interpreter, "product"), "3.0");
+        assert_eq!(global(&interpreter, "less"), "true");
+        assert_eq!(global(&interpreter, "greater"), "false");
+    }
+
+    #[test]
+    fn test_division() {
+        let interpreter = run("
+            var ints = 7 / 2;
+            var negative = -7 / 2;
+            var mixed = 7 / 2.0;
+            var floats = 1.0 / 4.0;
+        ");
+        assert_eq!(global(&interpreter, "ints"), "3");
+        assert_eq!(global(&interpreter, "negative"), "-3");
+        assert_eq!(global(&interpreter, "mixed"), "3.5");
+        assert_eq!(global(&interpreter, "floats"), "0.25");
+    }
+
+    #[test]
+    fn test_conversions() {
+        let interpreter = run("
+            var a = int(3.9);
+            var b = int(-3.9);
+            var c = int(\" 42 \");
+            var d = float(2);
+            var e = float(\"0.5\");
+            var f = int;
+        ");
+        assert_eq!(global(&interpreter, "a"), "3");
+        assert_eq!(global(&interpreter, "b"), "-3");
+        assert_eq!(global(&interpreter, "c"), "42");
+        assert_eq!(global(&interpreter, "d"), "2.0");
+        assert_eq!(global(&interpreter, "e"), "0.5");
+        assert_eq!(global(&interpreter, "f"), "<native fn int>");
+        assert_eq!(
+            run_error("int(\"abc\");"),
+            "1: Can't convert \"abc\" to an int."
+        );
+        assert_eq!(
+            run_error("float(true);"),
+            "1: Can't convert boolean to a float."
+        );
+    }
+
+    #[test]
+    fn test_integer_overflow() {
+        let interpreter = run("var big = 3000000000 * 3;");
+        assert_eq!(global(&interpreter, "big"), "9000000000");
+        assert_eq!(
+            run_error("var max = 9223372036854775807;\nmax + 1;"),
+            "2: Integer overflow in '+'."
+        );
+        assert_eq!(
+            run_error("-9223372036854775807 - 2;"),
+            "1: Integer overflow in '-'."
+        );
+        assert_eq!(
+            run_error("4611686018427387904 * 2;"),
+            "1: Integer overflow in '*'."
+        );
+        assert_eq!(
+            run_error("var min = -9223372036854775807 - 1;\n-min;"),
+            "2: Integer overflow in '-'."
+        );
+    }
+
+    #[test]
+    fn test_division_by_zero() {
+        assert_eq!(run_error("1 / 0;"), "1: Division by zero.");
+        let interpreter = run("var inf = 1.0 / 0; var negative = -1 / 0.0;");
+        assert_eq!(global(&interpreter, "inf"), "inf");
+        assert_eq!(global(&interpreter, "negative"), "-inf");
+    }
+
+    #[test]
+    fn test_closure_counter() {
+        let interpreter = run("
+            fun makeCounter() {
+                var count = 0;
+                fun increment() {
+                    count = count + 1;
+                    return count;
+                }
+                return increment;
+            }
+            var first = makeCounter();
+            var second = makeCounter();
+            first();
+            first();
+            var a = first();
+            var b = second();
+        ");
+        assert_eq!(global(&interpreter, "a"), "3");
+        assert_eq!(global(&interpreter, "b"), "1");
+    }
+
+    #[test]
+    fn test_closures_share_captured_variable() {
+        let interpreter = run("
+            class Pair {}
+            fun makePair() {
+                var shared = 0;
+                var pair = Pair();
+                pair.inc = fun () { shared = shared + 1; };
+                pair.get = fun () { return shared; };
+                return pair;
+            }
+            var pair = makePair();
+            pair.inc();
+            pair.inc();
+            var value = pair.get();
+        ");
+        assert_eq!(global(&interpreter, "value"), "2");
+    }
+
+    #[test]
+    fn test_call_depth_limit() {
+        // Reaching the limit needs more native stack than a test thread has.
+        let (overflow, caught, after) = std::thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .spawn(|| {
+                let overflow = run_error("fun f(n) {\n  return 1 + f(n + 1);\n}\nf(0);");
+                let interpreter = run("
+                    fun f(n) { return 1 + f(n + 1); }
+                    fun count(n) { if (n == 0) return 0; return 1 + count(n - 1); }
+                    var caught;
+                    try { f(0); } catch (e) { caught = e.message; }
+                    var after = count(990);
+                ");
+                (
+                    overflow,
+                    global(&interpreter, "caught"),
+                    global(&interpreter, "after"),
+                )
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(overflow, "2: Stack overflow.");
+        assert_eq!(caught, "Stack overflow.");
+        assert_eq!(after, "990");
+    }
+
+    #[test]
+    fn test_anonymous_functions() {
+        let interpreter = run("
+            fun apply(f, x) { return f(x); }
+            var doubled = apply(fun (x) { return x * 2; }, 21);
+            var immediate = fun (a, b) { return a - b; }(5, 3);
+            var f = fun () {};
+        ");
+        assert_eq!(global(&interpreter, "doubled"), "42");
+        assert_eq!(global(&interpreter, "immediate"), "2");
+        assert_eq!(global(&interpreter, "f"), "<anonymous fn>");
+    }
+
+    #[test]
+    fn test_list_literals_and_indexing() {
+        let interpreter = run("
+            var list = [1, \"two\", [3.0, true]];
+            var empty = [];
+            var first = list[0];
+            var last = list[-1];
+            var nested = list[2][1];
+            var size = len(list);
+            var quoted = [\"a\\\"b\", \"c\\\\d\"];
+        ");
+        assert_eq!(global(&interpreter, "list"), "[1, \"two\", [3.0, true]]");
+        assert_eq!(global(&interpreter, "empty"), "[]");
+        assert_eq!(global(&interpreter, "first"), "1");
+        assert_eq!(global(&interpreter, "last"), "[3.0, true]");
+        assert_eq!(global(&interpreter, "nested"), "true");
+        assert_eq!(global(&interpreter, "size"), "3");
+        assert_eq!(global(&interpreter, "quoted"), "[\"a\\\"b\", \"c\\\\d\"]");
+    }
+
+    #[test]
+    fn test_list_index_assignment_is_shared() {
+        let interpreter = run("
+            var a = [1, 2, 3];
+            var b = a;
+            b[0] = 10;
+            a[-1] = a[-1] * 2;
+            fun clear(list) { list[1] = 0; }
+            clear(a);
+        ");
+        assert_eq!(global(&interpreter, "a"), "[10, 0, 6]");
+        assert_eq!(global(&interpreter, "b"), "[10, 0, 6]");
+    }
+
+    #[test]
+    fn test_list_index_errors() {
+        assert_eq!(
+            run_error("var a = [1, 2];\na[2];"),
+            "2: Index 2 out of bounds for list of length 2."
+        );
+        assert_eq!(
+            run_error("[1, 2][-3] = 0;"),
+            "1: Index -3 out of bounds for list of length 2."
+        );
+        assert_eq!(
+            run_error("[1][\"0\"];"),
+            "1: List index must be an int, got string."
+        );
+        assert_eq!(run_error("1[0];"), "1: Can't index into int.");
+    }
+
+    #[test]
+    fn test_list_equality() {
+        let interpreter = run("
+            var same = [1, [2]] == [1, [2]];
+            var different = [1, 2] == [2, 1];
+        ");
+        assert_eq!(global(&interpreter, "same"), "true");
+        assert_eq!(global(&interpreter, "different"), "false");
+    }
+
+    #[test]
+    fn test_self_referential_lists() {
+        let interpreter = run("
+            var a = [1, 2];
+            a[0] = a;
+            var b = [a];
+            var c = [1];
+            var d = [c];
+            c[0] = d;
+            var same = c == d;
+            var e = [1];
+            var f = [1];
+            e[0] = e;
+            f[0] = f;
+            var cyclic_equal = e == f;
+        ");
+        assert_eq!(global(&interpreter, "a"), "[[...], 2]");
+        assert_eq!(global(&interpreter, "b"), "[[[...], 2]]");
+        assert_eq!(global(&interpreter, "c"), "[[[...]]]");
+        assert_eq!(global(&interpreter, "same"), "true");
+        assert_eq!(global(&interpreter, "cyclic_equal"), "true");
+    }
+
+    #[test]
+    fn test_deeply_nested_lists() {
+        // Freeing a deeply nested list must not recurse, even on a test
+        // thread's small stack.
+        let interpreter = run("
+            var l = [];
+            for (var i = 0; i < 100000; i++) l = [l];
+            l = pls_no;
+        ");
+        assert_eq!(global(&interpreter, "l"), "why am i nil?");
+
+        // Printing and comparing stop at the nesting limit instead.
+        let (printed, compared, within_limit) = std::thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .spawn(|| {
+                let source = "
+                    var a = [];
+                    var b = [];
+                    for (var i = 0; i < 20000; i++) { a = [a]; b = [b]; }
+                    var shallow = [[[1]]];
+                    var same = shallow == [[[1]]];
+                ";
+                let interpreter = run(source);
+                (
+                    run_error(&format!("{}\nprint a;", source.trim())),
+                    run_error(&format!("{}\nprint a == b;", source.trim())),
+                    global(&interpreter, "same"),
+                )
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(printed, "6: Value is nested too deeply to print.");
+        assert_eq!(compared, "6: Values are nested too deeply to compare.");
+        assert_eq!(within_limit, "true");
+    }
+
+    #[test]
+    fn test_map_literals_and_subscripts() {
+        let interpreter = run("
+            var m = {\"b\": 1, \"a\": 2, 3: [true], false: 0};
+            m[\"b\"] = 10;
+            m[\"c\"] = m[3][0];
+            var empty = {};
+            var a = m[\"a\"];
+            var keys = keys(m);
+            var size = len(m);
+        ");
+        assert_eq!(
+            global(&interpreter, "m"),
+            "{\"b\": 10, \"a\": 2, 3: [true], false: 0, \"c\": true}"
+        );
+        assert_eq!(global(&interpreter, "empty"), "{}");
+        assert_eq!(global(&interpreter, "a"), "2");
+        assert_eq!(
+            global(&interpreter, "keys"),
+            "[\"b\", \"a\", 3, false, \"c\"]"
+        );
+        assert_eq!(global(&interpreter, "size"), "5");
+    }
+
+    #[test]
+    fn test_in_operator() {
+        let interpreter = run("
+            var m = {\"a\": 1, 2: 2};
+            var hasA = \"a\" in m;
+            var hasB = \"b\" in m;
+            var hasTwo = 2 in m;
+            var inList = 3 in [1, 2, 3];
+        ");
+        assert_eq!(global(&interpreter, "hasA"), "true");
+        assert_eq!(global(&interpreter, "hasB"), "false");
+        assert_eq!(global(&interpreter, "hasTwo"), "true");
+        assert_eq!(global(&interpreter, "inList"), "true");
+        assert_eq!(
+            run_error("1 in 2;"),
+            "1: Unsupported operand types for 'in': int and int."
+        );
+    }
+
+    #[test]
+    fn test_map_errors() {
+        assert_eq!(
+            run_error("var m = {\"a\": 1};\nm[\"b\"];"),
+            "2: Key \"b\" not found in map."
+        );
+        assert_eq!(
+            run_error("var m = {1.5: 1};"),
+            "1: Map keys must be strings, ints or booleans, got float."
+        );
+        assert_eq!(
+            run_error("var m = {};\nm[[]] = 1;"),
+            "2: Map keys must be strings, ints or booleans, got list."
+        );
+    }
+
+    #[test]
+    fn test_self_referential_maps() {
+        let interpreter = run("
+            var m = {};
+            m[\"s\"] = m;
+            var l = [m];
+            m[\"l\"] = l;
+            var a = {\"x\": 1};
+            var b = {\"x\": 1};
+            a[\"x\"] = a;
+            b[\"x\"] = b;
+            var cyclic_equal = a == b;
+        ");
+        assert_eq!(global(&interpreter, "m"), "{\"s\": {...}, \"l\": [{...}]}");
+        assert_eq!(global(&interpreter, "l"), "[{\"s\": {...}, \"l\": [...]}]");
+        assert_eq!(global(&interpreter, "cyclic_equal"), "true");
+    }
+
+    #[test]
+    fn test_map_equality_ignores_order() {
+        let interpreter = run("var same = {\"a\": 1, \"b\": 2} == {\"b\": 2, \"a\": 1};");
+        assert_eq!(global(&interpreter, "same"), "true");
+    }
+
+    #[test]
+    fn test_string_interpolation() {
+        let interpreter = run("
+            var a = 1;
+            var b = 2.5;
+            var names = [\"x\", \"y\"];
+            var nothing;
+            var s = \"total: ${a + b}, ${names} ${\"nested ${a}\"}${nothing}\";
+            var letter = \"\"\"
+                a = ${a}
+                  b = ${b}
+                \"\"\";
+        ");
+        assert_eq!(
+            global(&interpreter, "s"),
+            "total: 3.5, [\"x\", \"y\"] nested 1why am i nil?"
+        );
+        assert_eq!(global(&interpreter, "letter"), "a = 1\n  b = 2.5");
+    }
+
+    #[test]
+    fn test_modulo_power_and_integer_division() {
+        let interpreter = run("
+            var mods = [7 % 3, -7 % 3, 7 % -3, -7 % -3, 7.5 % 2, -1.5 % 1];
+            var divs = [7 ~/ 2, -7 ~/ 2, 7 ~/ -2, 7.5 ~/ 2];
+            var powers = [-2 ** 2, 2 ** 3 ** 2, 2 ** -1, 2.0 ** 0.5 == 2 ** 0.5];
+        ");
+        assert_eq!(global(&interpreter, "mods"), "[1, 2, -2, -1, 1.5, 0.5]");
+        assert_eq!(global(&interpreter, "divs"), "[3, -4, -4, 3.0]");
+        assert_eq!(global(&interpreter, "powers"), "[-4, 512, 0.5, true]");
+    }
+
+    #[test]
+    fn test_modulo_power_and_integer_division_errors() {
+        assert_eq!(run_error("1 % 0;"), "1: Division by zero.");
+        assert_eq!(run_error("1 ~/ 0;"), "1: Division by zero.");
+        assert_eq!(run_error("2 ** 63;"), "1: Integer overflow in '**'.");
+        assert_eq!(
+            run_error("\"a\" % 2;"),
+            "1: Unsupported operand types for '%': string and int."
+        );
+    }
+
+    #[test]
+    fn test_compound_assignment() {
+        let interpreter = run("
+            var a = 10;
+            a += 5;
+            a -= 3;
+            a *= 2;
+            a /= 4;
+            a %= 4;
+            var s = \"ab\";
+            s += \"c\";
+            class Box {}
+            var box = Box();
+            box.n = 1;
+            box.n += 41;
+            var n = box.n;
+            var m = {\"k\": [1, 2]};
+            m[\"k\"][1] += 10;
+            var r = (a += 1);
+        ");
+        assert_eq!(global(&interpreter, "a"), "3");
+        assert_eq!(global(&interpreter, "r"), "3");
+        assert_eq!(global(&interpreter, "s"), "abc");
+        assert_eq!(global(&interpreter, "m"), "{\"k\": [1, 12]}");
+        assert_eq!(global(&interpreter, "n"), "42");
+    }
+
+    #[test]
+    fn test_increment_and_decrement() {
+        let interpreter = run("
+            var i = 0;
+            var pre = ++i;
+            var post = i++;
+            var list = [5];
+            var calls = 0;
+            fun index() { calls = calls + 1; return 0; }
+            list[index()]--;
+            --list[index()];
+        ");
+        assert_eq!(global(&interpreter, "i"), "2");
+        assert_eq!(global(&interpreter, "pre"), "1");
+        assert_eq!(global(&interpreter, "post"), "1");
+        assert_eq!(global(&interpreter, "list"), "[3]");
+        assert_eq!(global(&interpreter, "calls"), "2");
+        assert_eq!(
+            run_error("var s = \"a\";\ns++;"),
+            "2: Unsupported operand type for '++': string."
+        );
+        assert_eq!(
+            run_error("var n;\n--n;"),
+            "2: Unsupported operand type for '--': nil."
+        );
+        assert_eq!(
+            run_error("var big = 9223372036854775807;\nbig++;"),
+            "2: Integer overflow in '++'."
+        );
+    }
+
+    #[test]
+    fn test_conditional_and_coalesce() {
+        let interpreter = run("
+            var calls = 0;
+            fun touch(value) { calls = calls + 1; return value; }
+            var nothing;
+            var big = 5 > 3 ? touch(\"yes\") : touch(\"no\");
+            var sign = -2 > 0 ? 1 : -2 < 0 ? -1 : 0;
+            var fallback = nothing ?? touch(\"default\");
+            var kept = false ?? touch(true);
+            var zero = 0 ?? 1;
+        ");
+        assert_eq!(global(&interpreter, "big"), "yes");
+        assert_eq!(global(&interpreter, "sign"), "-1");
+        assert_eq!(global(&interpreter, "fallback"), "default");
+        assert_eq!(global(&interpreter, "kept"), "false");
+        assert_eq!(global(&interpreter, "zero"), "0");
+        assert_eq!(global(&interpreter, "calls"), "2");
+    }
+
+    #[test]
+    fn test_bitwise_operators() {
+        let interpreter = run("
+            var bits = [12 & 10, 12 | 10, 12 ^ 10, ~0, 1 << 62, -16 >> 2, 5 >> 63];
+        ");
+        assert_eq!(
+            global(&interpreter, "bits"),
+            "[8, 14, 6, -1, 4611686018427387904, -4, 0]"
+        );
+        assert_eq!(
+            run_error("1.5 & 1;"),
+            "1: Unsupported operand types for '&': float and int."
+        );
+        assert_eq!(
+            run_error("~1.0;"),
+            "1: Unsupported operand type for '~': float."
+        );
+        assert_eq!(
+            run_error("1 << 64;"),
+            "1: Shift amount must be between 0 and 63, got 64."
+        );
+        assert_eq!(
+            run_error("1 >> -1;"),
+            "1: Shift amount must be between 0 and 63, got -1."
+        );
+    }
+
+    #[test]
+    fn test_catching_runtime_errors() {
+        let interpreter = run("
+            var e1;
+            try { 1 / 0; } catch (e) { e1 = e; }
+            var divide = e1.message;
+            var divideLine = e1.line;
+            try { missing; } catch (e) { e1 = e; }
+            var undefined = e1.message;
+            try {
+                \"a\" - 1;
+            } catch (e) { e1 = e; }
+            var types = e1.message;
+            var typesLine = e1.line;
+        ");
+        assert_eq!(global(&interpreter, "divide"), "Division by zero.");
+        assert_eq!(global(&interpreter, "divideLine"), "3");
+        assert_eq!(
+            global(&interpreter, "undefined"),
+            "Undefined variable 'missing'."
+        );
+        assert_eq!(
+            global(&interpreter, "types"),
+            "Unsupported operand types for '-': string and int."
+        );
+        assert_eq!(global(&interpreter, "typesLine"), "9");
+    }
+
+    #[test]
+    fn test_throw_and_finally() {
+        let interpreter = run("
+            var log = \"\";
+            fun record(entry) { log = \"${log}${entry};\"; }
+            fun thrower() { throw {\"code\": 7}; }
+            try { thrower(); } catch (e) { record(e[\"code\"]); } finally { record(\"finally\"); }
+            fun early() {
+                try { return \"body\"; } finally { record(\"cleanup\"); }
+            }
+            var returned = early();
+            for (var i = 0; i < 3; i = i + 1) {
+                try { if (i == 1) break; } finally { record(i); }
+            }
+            try {
+                try { throw Error(\"inner\"); } finally { record(\"inner finally\"); }
+            } catch (e) { record(e.message); }
+            try {
+                try { throw 1; } catch (e) { throw e + 1; }
+            } catch (e) { record(e); }
+        ");
+        assert_eq!(
+            global(&interpreter, "log"),
+            "7;finally;cleanup;0;1;inner finally;inner;2;"
+        );
+        assert_eq!(global(&interpreter, "returned"), "body");
+    }
+
+    #[test]
+    fn test_error_class() {
+        let interpreter = run("
+            class NotFound < Error {
+                init(path) {
+                    super.init(\"No such file: ${path}\");
+                    this.path = path;
+                }
+            }
+            var plain = Error(\"boom\");
+            var missing;
+            try {
+                throw NotFound(\"a.txt\");
+            } catch (e) { missing = e; }
+            var missingLine = missing.line;
+            var runtime;
+            try { 1 / 0; } catch (e) { runtime = e; }
+            class Quiet < Error { init() {} }
+            var quiet = Quiet();
+        ");
+        assert_eq!(global(&interpreter, "plain"), "Error: boom");
+        assert_eq!(
+            global(&interpreter, "missing"),
+            "NotFound: No such file: a.txt"
+        );
+        assert_eq!(global(&interpreter, "missingLine"), "11");
+        assert_eq!(global(&interpreter, "runtime"), "Error: Division by zero.");
+        assert_eq!(global(&interpreter, "quiet"), "<Quiet instance>");
+    }
+
+    #[test]
+    fn test_uncaught_exception_stops_interpreting() {
+        let tokens = Scanner::new("var a = 1; throw \"boom\"; a = 2;")
+            .scan_tokens()
+            .clone();
+        let stmts = Parser::new(tokens).parse();
+        let mut interpreter = Interpreter::new();
+        assert!(!interpreter.interpret(&stmts));
+        assert_eq!(global(&interpreter, "a"), "1");
+    }
+
+    /// Writes `files` into a fresh temporary directory and runs its `main.amm`,
+    /// returning the interpreter and the first runtime error, if any.
+    fn run_files(
+        test: &str,
+        files: &[(&str, &str)],
+        module_paths: &[&str],
+    ) -> (Interpreter, Option<String>) {
+        let dir = std::env::temp_dir().join(format!("amm-{}-{}", test, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        for (name, source) in files {
+            let path = dir.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, source).unwrap();
+        }
+        let main = dir.join("main.amm");
+        let tokens = Scanner::new(&fs::read_to_string(&main).unwrap())
+            .scan_tokens()
+            .clone();
+        let stmts = Parser::new(tokens).parse();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_main_file(&main);
+        interpreter.module_paths = module_paths.iter().map(|p| dir.join(p)).collect();
+        let mut error = None;
+        for stmt in &stmts {
+            if let Err(Unwind::Error(err)) = interpreter.execute(stmt) {
+                error = Some(format!("{}: {}", err.token.line, err.message));
+                break;
+            }
+        }
+        (interpreter, error)
+    }
+
+    #[test]
+    fn test_imports() {
+        let (interpreter, error) = run_files(
+            "imports",
+            &[
+                (
+                    "main.amm",
+                    "import \"lib/math.amm\" as math;
+                    import { square, Counter as Tally } from \"lib/math.amm\";
+                    import \"strings.amm\" as strings;
+                    var area = math.square(3) + square(1);
+                    var counter = Tally();
+                    counter.bump();
+                    var count = counter.n;
+                    var cached = math == math;
+                    var greeting = strings.greet(\"amm\");",
+                ),
+                (
+                    "lib/math.amm",
+                    "import { offset } from \"helpers.amm\";
+                    var hidden = 2;
+                    export fun square(x) { return x * x + offset; }
+                    export class Counter { init() { this.n = 0; } bump() { this.n += hidden; } }",
+                ),
+                ("lib/helpers.amm", "export var offset = 0;"),
+                (
+                    "path/strings.amm",
+                    "export fun greet(name) { return \"hi ${name}\"; }",
+                ),
+            ],
+            &["path"],
+        );
+        assert_eq!(error, None);
+        assert_eq!(global(&interpreter, "area"), "10");
+        assert_eq!(global(&interpreter, "count"), "2");
+        assert_eq!(global(&interpreter, "cached"), "true");
+        assert_eq!(global(&interpreter, "greeting"), "hi amm");
+        assert!(!is_defined(&interpreter, "hidden"));
+        assert_eq!(interpreter.modules.len(), 3);
+    }
+
+    #[test]
+    fn test_import_inside_function() {
+        let (interpreter, error) = run_files(
+            "import-inside-function",
+            &[
+                (
+                    "main.amm",
+                    "import \"lib/mod.amm\" as m;
+                    var loaded = m.load();",
+                ),
+                (
+                    "lib/mod.amm",
+                    "export fun load() { import \"helper.amm\" as h; return h.v; }",
+                ),
+                ("lib/helper.amm", "export var v = \"lib\";"),
+                ("helper.amm", "export var v = \"main\";"),
+            ],
+            &[],
+        );
+        assert_eq!(error, None);
+        assert_eq!(global(&interpreter, "loaded"), "lib");
+        assert!(interpreter.current_file.unwrap().ends_with("main.amm"));
+    }
+
+    #[test]
+    fn test_import_errors() {
+        let (_, error) = run_files(
+            "import-missing",
+            &[("main.amm", "var a = 1;\nimport \"nowhere.amm\" as n;")],
+            &[],
+        );
+        assert!(error
+            .unwrap()
+            .starts_with("2: Module \"nowhere.amm\" not found (searched "));
+
+        let (_, error) = run_files(
+            "import-private",
+            &[
+                ("main.amm", "import { hidden } from \"lib.amm\";"),
+                ("lib.amm", "var hidden = 1;"),
+            ],
+            &[],
+        );
+        assert_eq!(
+            error.unwrap(),
+            "1: Module 'lib.amm' has no export 'hidden'."
+        );
+
+        let (_, error) = run_files(
+            "import-cycle",
+            &[
+                ("main.amm", "import \"a.amm\" as a;"),
+                ("a.amm", "import \"b.amm\" as b;"),
+                ("b.amm", "import \"a.amm\" as a;"),
+            ],
+            &[],
+        );
+        let error = error.unwrap();
+        assert!(
+            error.starts_with(
+                "1: In module \"a.amm\", line 1: In module \"b.amm\", line 1: Import cycle: "
+            ),
+            "{}",
+            error
+        );
+        assert!(error.contains("a.amm -> ") && error.contains("b.amm -> "));
+        assert!(error.ends_with("a.amm."));
+
+        let (_, error) = run_files(
+            "import-runtime-error",
+            &[
+                ("main.amm", "var a = 1;\nimport \"lib.amm\" as lib;"),
+                ("lib.amm", "import \"util.amm\" as util;"),
+                ("util.amm", "var x = 1;\n\nprint undefinedThing;"),
+            ],
+            &[],
+        );
+        assert_eq!(
+            error.unwrap(),
+            "2: In module \"lib.amm\", line 1: In module \"util.amm\", line 3: \
+             Undefined variable 'undefinedThing'."
+        );
+
+        let (_, error) = run_files(
+            "import-parse-error",
+            &[
+                ("main.amm", "import \"bad.amm\" as bad;"),
+                ("bad.amm", "var = 1;"),
+            ],
+            &[],
+        );
+        assert_eq!(error.unwrap(), "1: Module \"bad.amm\" has errors.");
+    }
+
+    #[test]
+    fn test_constants() {
+        let interpreter = run("
+            const limit = 10;
+            const config = {\"debug\": false};
+            config[\"debug\"] = true;
+            var doubled = limit * 2;
+        ");
+        assert_eq!(global(&interpreter, "doubled"), "20");
+        assert_eq!(global(&interpreter, "config"), "{\"debug\": true}");
+        assert_eq!(
+            run_error("fun reset() { limit = 0; }\nconst limit = 10;\nreset();"),
+            "1: Can't assign to constant 'limit'."
+        );
+        assert_eq!(
+            run_error("fun bump() { limit++; }\nconst limit = 10;\nbump();"),
+            "1: Can't assign to constant 'limit'."
+        );
+        assert_eq!(
+            run_error("fun reset() { limit = 0; }\nlet limit = 10;\nreset();"),
+            "1: Can't assign to constant 'limit'."
+        );
+    }
+
+    #[test]
+    fn test_redeclaring_constant_on_later_repl_line() {
+        let mut interpreter = Interpreter::new();
+        let mut errors = Vec::new();
+        for line in [
+            "const a = 1;",
+            "var a = 2;",
+            "a = 3;",
+            "fun a() {}",
+            "{ var a = 4; }",
+        ] {
+            let tokens = Scanner::new(line).scan_tokens().clone();
+            for stmt in &Parser::new(tokens).parse() {
+                if let Err(Unwind::Error(err)) = interpreter.execute(stmt) {
+                    errors.push(err.message);
+                }
+            }
+        }
+        assert_eq!(
+            errors,
+            [
+                "Can't redeclare constant 'a'.",
+                "Can't assign to constant 'a'.",
+                "Can't redeclare constant 'a'.",
+            ]
+        );
+        assert_eq!(global(&interpreter, "a"), "1");
+    }
+
+    #[test]
+    fn test_match() {
+        let interpreter = run("
+            fun describe(value) {
+                return match (value) {
+                    0 => \"zero\",
+                    1 | 2 | 3 => \"small\",
+                    -1 => \"minus one\",
+                    \"a\" | \"b\" => \"letter\",
+                    [x, y] if x == y => \"pair of ${x}\",
+                    [x, [y, _]] => \"nested ${x} ${y}\",
+                    [x, y] => \"pair ${x} ${y}\",
+                    pls_no => \"nothing\",
+                    n if n == 500 => \"five hundred\",
+                    _ => \"other\",
+                };
+            }
+            var zero = describe(0);
+            var small = describe(2);
+            var float_small = describe(3.0);
+            var negative = describe(-1);
+            var letter = describe(\"b\");
+            var same = describe([4, 4]);
+            var nested = describe([1, [2, 3]]);
+            var pair = describe([1, 2]);
+            var big = describe(500);
+            var nothing = describe(pls_no);
+            var other = describe(50);
+            var triple = describe([1, 2, 3]);
+
+            var log = \"\";
+            var x = \"outer\";
+            match (7) {
+                x if x > 5 => { log = \"big ${x}\"; }
+                _ => { log = \"small\"; }
+            }
+        ");
+        assert_eq!(global(&interpreter, "zero"), "zero");
+        assert_eq!(global(&interpreter, "small"), "small");
+        assert_eq!(global(&interpreter, "float_small"), "small");
+        assert_eq!(global(&interpreter, "negative"), "minus one");
+        assert_eq!(global(&interpreter, "letter"), "letter");
+        assert_eq!(global(&interpreter, "same"), "pair of 4");
+        assert_eq!(global(&interpreter, "nested"), "nested 1 2");
+        assert_eq!(global(&interpreter, "pair"), "pair 1 2");
+        assert_eq!(global(&interpreter, "big"), "five hundred");
+        assert_eq!(global(&interpreter, "nothing"), "nothing");
+        assert_eq!(global(&interpreter, "other"), "other");
+        assert_eq!(global(&interpreter, "triple"), "other");
+        assert_eq!(global(&interpreter, "log"), "big 7");
+        assert_eq!(global(&interpreter, "x"), "outer");
+    }
+
+    #[test]
+    fn test_match_without_matching_arm() {
+        assert_eq!(
+            run_error("var x = match ([1]) {\n[] => 0,\n[a, b] => 2\n};"),
+            "1: No match arm for value [1]."
+        );
+        let interpreter = run("
+            var caught;
+            try {
+                match (\"z\") { \"a\" => 1 }
+            } catch (e) {
+                caught = e.message;
+            }
+        ");
+        assert_eq!(
+            global(&interpreter, "caught"),
+            "No match arm for value \"z\"."
+        );
+    }
 }
@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use super::interpreter::Object;
+
+/// The subset of values that can be used as map keys.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum MapKey {
+    String(String),
+    Int(i64),
+    Boolean(bool),
+}
+
+impl MapKey {
+    pub fn from_object(object: &Object) -> Option<MapKey> {
+        match object {
+            Object::String(s) => Some(MapKey::String(s.clone())),
+            Object::Int(i) => Some(MapKey::Int(*i)),
+            Object::Boolean(b) => Some(MapKey::Boolean(*b)),
+            _ => None,
+        }
+    }
+
+    pub fn to_object(&self) -> Object {
+        match self {
+            MapKey::String(s) => Object::String(s.clone()),
+            MapKey::Int(i) => Object::Int(*i),
+            MapKey::Boolean(b) => Object::Boolean(*b),
+        }
+    }
+}
+
+/// A map that remembers insertion order, so iterating or printing it is
+/// deterministic.
+#[derive(Default)]
+pub struct Map {
+    entries: Vec<(MapKey, Object)>,
+    positions: HashMap<MapKey, usize>,
+}
+
+impl Map {
+    pub fn new() -> Map {
+        Map::default()
+    }
+
+    pub fn get(&self, key: &MapKey) -> Option<&Object> {
+        self.positions.get(key).map(|&i| &self.entries[i].1)
+    }
+
+    pub fn contains_key(&self, key: &MapKey) -> bool {
+        self.positions.contains_key(key)
+    }
+
+    /// Overwrites the value of an existing key in place, keeping its position.
+    pub fn insert(&mut self, key: MapKey, value: Object) {
+        match self.positions.get(&key) {
+            Some(&i) => self.entries[i].1 = value,
+            None => {
+                self.positions.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &MapKey> {
+        self.entries.iter().map(|(key, _)| key)
+    }
+
+    /// Maps are equal when they hold equal values under the same keys,
+    /// regardless of insertion order. `comparing` and the result are as for
+    /// `Object::equals`.
+    pub fn equals(&self, other: &Map, comparing: &mut Vec<(*const (), *const ())>) -> Option<bool> {
+        if self.len() != other.len() {
+            return Some(false);
+        }
+        for (key, value) in &self.entries {
+            match other.get(key) {
+                Some(other) if value.equals(other, comparing)? => {}
+                _ => return Some(false),
+            }
+        }
+        Some(true)
+    }
+
+    /// Formats the entries; `seen` and the result are as for
+    /// `Object::repr_within`, and `seen` must already hold this map.
+    pub fn repr_within(&self, seen: &mut Vec<*const ()>) -> Option<String> {
+        let entries = self
+            .entries
+            .iter()
+            .map(|(key, value)| {
+                Some(format!(
+                    "{}: {}",
+                    key.to_object().repr(),
+                    value.repr_within(seen)?
+                ))
+            })
+            .collect::<Option<Vec<String>>>()?;
+        Some(format!("{{{}}}", entries.join(", ")))
+    }
+
+    /// Empties the map, handing back its values.
+    pub fn take_values(&mut self) -> Vec<Object> {
+        self.positions.clear();
+        std::mem::take(&mut self.entries)
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect()
+    }
+}
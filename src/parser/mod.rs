@@ -1,6 +1,12 @@
 mod ast_printer;
+mod callable;
+mod class;
 mod environment;
 mod expr;
 pub mod interpreter;
+mod map;
+mod module;
+mod natives;
 pub mod parser;
+mod pattern;
 mod stmt;
@@ -0,0 +1,104 @@
+use core::fmt;
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    env,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::lexer::token::Token;
+
+use super::{
+    environment::Environment,
+    interpreter::{InterpreterError, Object, Result},
+    stmt::Stmt,
+};
+
+/// A source file that has been imported and run. It keeps its own globals, of
+/// which only the exported names can be read from outside.
+pub struct Module {
+    path: PathBuf,
+    globals: Rc<RefCell<Environment>>,
+    exports: HashSet<String>,
+}
+
+impl Module {
+    pub fn new(
+        path: PathBuf,
+        globals: Rc<RefCell<Environment>>,
+        exports: HashSet<String>,
+    ) -> Module {
+        Module {
+            path,
+            globals,
+            exports,
+        }
+    }
+
+    pub fn get(&self, name: &Token) -> Result<Object> {
+        if !self.exports.contains(&name.lexeme) {
+            return Err(InterpreterError::new(
+                name,
+                &format!("Module '{}' has no export '{}'.", self.name(), name.lexeme),
+            )
+            .into());
+        }
+        self.globals.borrow().get(name)
+    }
+
+    fn name(&self) -> String {
+        self.path.file_name().map_or_else(
+            || self.path.display().to_string(),
+            |name| name.to_string_lossy().to_string(),
+        )
+    }
+}
+
+impl fmt::Display for Module {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<module {}>", self.name())
+    }
+}
+
+/// The directories listed in `AMM_PATH`, searched after the importing file's
+/// own directory.
+pub fn search_paths() -> Vec<PathBuf> {
+    env::var_os("AMM_PATH")
+        .map(|paths| env::split_paths(&paths).collect())
+        .unwrap_or_default()
+}
+
+/// Finds the file `path` refers to, trying `base` first and then each of the
+/// `search_paths`. On failure, returns every location that was tried.
+pub fn resolve(
+    path: &str,
+    base: &Path,
+    search_paths: &[PathBuf],
+) -> std::result::Result<PathBuf, Vec<PathBuf>> {
+    let candidates: Vec<PathBuf> = std::iter::once(base)
+        .chain(search_paths.iter().map(PathBuf::as_path))
+        .map(|dir| dir.join(path))
+        .collect();
+    match candidates.iter().find(|candidate| candidate.is_file()) {
+        Some(found) => Ok(found.canonicalize().unwrap_or_else(|_| found.clone())),
+        None => Err(candidates),
+    }
+}
+
+/// The names declared by the top-level `export` statements of a module.
+pub fn exports(stmts: &[Stmt]) -> HashSet<String> {
+    stmts
+        .iter()
+        .filter_map(|stmt| match stmt {
+            Stmt::Export { declaration } => match declaration.as_ref() {
+                Stmt::Var { name, .. }
+                | Stmt::Const { name, .. }
+                | Stmt::Function { name, .. }
+                | Stmt::Class { name, .. } => Some(name.lexeme.clone()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
@@ -0,0 +1,136 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::lexer::{token::Token, token_type::TokenType};
+
+use super::{
+    callable::{Function, NativeFunction},
+    class::{Class, Instance},
+    environment::Environment,
+    expr::Expr,
+    interpreter::{InterpreterError, Object, Result},
+    stmt::Stmt,
+};
+
+/// Defines the built-in functions and the `Error` class in the global
+/// environment.
+pub fn define(globals: &mut Environment, error_class: &Rc<Class>) {
+    let natives = [
+        NativeFunction::new("int", 1, int),
+        NativeFunction::new("float", 1, float),
+        NativeFunction::new("len", 1, len),
+        NativeFunction::new("keys", 1, keys),
+    ];
+    for native in natives {
+        globals.define(
+            native.name().to_string(),
+            Object::NativeFunction(Rc::new(native)),
+        );
+    }
+    globals.define("Error".to_string(), Object::Class(error_class.clone()));
+}
+
+/// Converts to an int, truncating floats toward zero.
+fn int(paren: &Token, arguments: Vec<Object>) -> Result<Object> {
+    match &arguments[0] {
+        Object::Int(i) => Ok(Object::Int(*i)),
+        Object::Float(fl) => {
+            let truncated = fl.trunc();
+            // i64::MAX isn't representable as a float; 2^63 is the first value past it.
+            if truncated >= i64::MIN as f64 && truncated < i64::MAX as f64 {
+                Ok(Object::Int(truncated as i64))
+            } else {
+                Err(
+                    InterpreterError::new(paren, &format!("Can't convert {:?} to an int.", fl))
+                        .into(),
+                )
+            }
+        }
+        Object::String(s) => s.trim().parse::<i64>().map(Object::Int).map_err(|_| {
+            InterpreterError::new(paren, &format!("Can't convert \"{}\" to an int.", s)).into()
+        }),
+        other => Err(InterpreterError::new(
+            paren,
+            &format!("Can't convert {} to an int.", other.type_name()),
+        )
+        .into()),
+    }
+}
+
+fn float(paren: &Token, arguments: Vec<Object>) -> Result<Object> {
+    match &arguments[0] {
+        Object::Int(i) => Ok(Object::Float(*i as f64)),
+        Object::Float(fl) => Ok(Object::Float(*fl)),
+        Object::String(s) => s.trim().parse::<f64>().map(Object::Float).map_err(|_| {
+            InterpreterError::new(paren, &format!("Can't convert \"{}\" to a float.", s)).into()
+        }),
+        other => Err(InterpreterError::new(
+            paren,
+            &format!("Can't convert {} to a float.", other.type_name()),
+        )
+        .into()),
+    }
+}
+
+fn len(paren: &Token, arguments: Vec<Object>) -> Result<Object> {
+    match &arguments[0] {
+        Object::String(s) => Ok(Object::Int(s.chars().count() as i64)),
+        Object::List(list) => Ok(Object::Int(list.borrow().len() as i64)),
+        Object::Map(map) => Ok(Object::Int(map.borrow().len() as i64)),
+        other => Err(InterpreterError::new(
+            paren,
+            &format!("Can't take the length of {}.", other.type_name()),
+        )
+        .into()),
+    }
+}
+
+/// Lists a map's keys in insertion order.
+fn keys(paren: &Token, arguments: Vec<Object>) -> Result<Object> {
+    match &arguments[0] {
+        Object::Map(map) => {
+            let keys = map.borrow().keys().map(|key| key.to_object()).collect();
+            Ok(Object::List(Rc::new(RefCell::new(keys))))
+        }
+        other => Err(InterpreterError::new(
+            paren,
+            &format!("Can't list the keys of {}.", other.type_name()),
+        )
+        .into()),
+    }
+}
+
+/// Builds the `Error` class, equivalent to
+/// `class Error { init(message) { this.message = message; } }`.
+pub fn error_class() -> Rc<Class> {
+    let token = |token_type, lexeme: &str| Token::new(token_type, lexeme.to_string(), None, 0);
+    let message = token(TokenType::IDENTIFIER, "message");
+    let body = [Stmt::Expr(Expr::Set {
+        object: Box::new(Expr::This {
+            keyword: token(TokenType::THIS, "this"),
+        }),
+        name: message.clone(),
+        value: Box::new(Expr::Variable {
+            name: message.clone(),
+        }),
+    })];
+    let init = Function::new(
+        Some(&token(TokenType::IDENTIFIER, "init")),
+        &[message],
+        &body,
+        Rc::new(RefCell::new(Environment::new())),
+        true,
+        None,
+    );
+    let methods = HashMap::from([("init".to_string(), Rc::new(init))]);
+    Rc::new(Class::new_error(methods))
+}
+
+/// Builds the value a caught runtime error is bound to: an `Error` instance
+/// with `message` and `line` fields.
+pub fn error_object(class: &Rc<Class>, message: &str, line: u32) -> Object {
+    let mut instance = Instance::new(class.clone());
+    let field = |name: &str| Token::new(TokenType::IDENTIFIER, name.to_string(), None, line);
+    instance.set(&field("message"), Object::String(message.to_string()));
+    instance.set(&field("line"), Object::Int(i64::from(line)));
+    Object::Instance(Rc::new(RefCell::new(instance)))
+}
@@ -1,4 +1,5 @@
 use core::fmt;
+use std::collections::HashMap;
 
 use crate::{
     lexer::{
@@ -8,11 +9,41 @@ use crate::{
     log,
 };
 
-use super::{expr::Expr, stmt::Stmt};
+use super::{
+    expr::Expr,
+    pattern::{ArmBody, MatchArm, Pattern},
+    stmt::Stmt,
+};
 
 pub struct Parser {
     current: usize,
     tokens: Vec<Token>,
+    had_error: bool,
+    current_function: FunctionType,
+    current_class: ClassType,
+    /// Labels of the loops enclosing the current statement, innermost last.
+    loops: Vec<Option<String>>,
+    /// The names declared in each enclosing scope, innermost last, mapped to
+    /// whether they are constants. Assignments to names found here are checked
+    /// now; any others are left to the interpreter.
+    scopes: Vec<HashMap<String, bool>>,
+    /// The imported module being parsed, named in error messages.
+    module: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum FunctionType {
+    None,
+    Function,
+    Method,
+    Initializer,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ClassType {
+    None,
+    Class,
+    Subclass,
 }
 
 struct ParserError {
@@ -31,16 +62,39 @@ type Result<T> = std::result::Result<T, ParserError>;
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
         assert_ne!(tokens.len(), 0);
-        Self { current: 0, tokens }
+        Self {
+            current: 0,
+            tokens,
+            had_error: false,
+            current_function: FunctionType::None,
+            current_class: ClassType::None,
+            loops: Vec::new(),
+            scopes: vec![HashMap::new()],
+            module: None,
+        }
+    }
+
+    pub fn set_module(&mut self, name: &str) {
+        self.module = Some(name.to_string());
     }
 
     pub fn parse(&mut self) -> Vec<Stmt> {
         let mut stmts = Vec::new();
         while !self.is_at_end() {
-            match self.declaration() {
+            let declaration = if self.match_token(vec![token_type::TokenType::EXPORT]) {
+                self.export_declaration()
+            } else {
+                self.declaration()
+            };
+            match declaration {
                 Ok(stmt) => stmts.push(stmt),
                 Err(err) => {
-                    log::log_message::print_code_error(err.token.line, &err.message);
+                    self.had_error = true;
+                    let message = match &self.module {
+                        Some(module) => format!("In module \"{}\": {}", module, err.message),
+                        None => err.message,
+                    };
+                    log::log_message::print_code_error(err.token.line, &message);
                     self.synchronize();
                 }
             }
@@ -49,13 +103,189 @@ impl Parser {
         stmts
     }
 
+    pub fn had_error(&self) -> bool {
+        self.had_error
+    }
+
+    fn export_declaration(&mut self) -> Result<Stmt> {
+        let exportable = self.check(token_type::TokenType::CLASS)
+            || self.check(token_type::TokenType::VAR)
+            || self.check(token_type::TokenType::CONST)
+            || self.check(token_type::TokenType::LET)
+            || (self.check(token_type::TokenType::FUN)
+                && self.check_next(token_type::TokenType::IDENTIFIER));
+        if !exportable {
+            return Err(ParserError {
+                message: "Expect class, function or variable declaration after 'export'."
+                    .to_string(),
+                token: self.peek().clone(),
+            });
+        }
+        Ok(Stmt::Export {
+            declaration: Box::new(self.declaration()?),
+        })
+    }
+
     fn declaration(&mut self) -> Result<Stmt> {
+        if self.match_token(vec![token_type::TokenType::EXPORT]) {
+            return Err(ParserError {
+                message: "Can only export top-level declarations.".to_string(),
+                token: self.previous().clone(),
+            });
+        }
+        if self.match_token(vec![token_type::TokenType::CLASS]) {
+            return self.class_declaration();
+        }
+        if self.check(token_type::TokenType::FUN)
+            && self.check_next(token_type::TokenType::IDENTIFIER)
+        {
+            self.advance();
+            return self.function("function");
+        }
         if self.match_token(vec![token_type::TokenType::VAR]) {
             return self.var_declaration();
         }
+        // `let` is a synonym for `const`.
+        if self.match_token(vec![
+            token_type::TokenType::CONST,
+            token_type::TokenType::LET,
+        ]) {
+            return self.const_declaration();
+        }
         self.statement()
     }
 
+    /// Records `name` in the innermost scope. Shadowing a constant from an
+    /// outer scope is fine, but redeclaring one in the same scope is not.
+    fn declare(&mut self, name: &Token, constant: bool) -> Result<()> {
+        let scope = self
+            .scopes
+            .last_mut()
+            .expect("the global scope is never popped");
+        if scope.get(&name.lexeme) == Some(&true) {
+            return Err(ParserError {
+                message: format!("Can't redeclare constant '{}'.", name.lexeme),
+                token: name.clone(),
+            });
+        }
+        scope.insert(name.lexeme.clone(), constant);
+        Ok(())
+    }
+
+    fn check_assignable(&self, name: &Token) -> Result<()> {
+        let constant = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&name.lexeme));
+        if constant == Some(&true) {
+            return Err(ParserError {
+                message: format!("Can't assign to constant '{}'.", name.lexeme),
+                token: name.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    fn class_declaration(&mut self) -> Result<Stmt> {
+        let name = self.consume(token_type::TokenType::IDENTIFIER, "Expect class name.")?;
+        self.declare(&name, false)?;
+        let superclass = if self.match_token(vec![token_type::TokenType::LESS]) {
+            let superclass =
+                self.consume(token_type::TokenType::IDENTIFIER, "Expect superclass name.")?;
+            Some(Expr::Variable { name: superclass })
+        } else {
+            None
+        };
+        self.consume(
+            token_type::TokenType::LEFT_BRACE,
+            "Expect '{' before class body.",
+        )?;
+
+        let class_type = if superclass.is_some() {
+            ClassType::Subclass
+        } else {
+            ClassType::Class
+        };
+        let enclosing_class = std::mem::replace(&mut self.current_class, class_type);
+        let methods = self.methods();
+        self.current_class = enclosing_class;
+        let methods = methods?;
+
+        self.consume(
+            token_type::TokenType::RIGHT_BRACE,
+            "Expect '}' after class body.",
+        )?;
+        Ok(Stmt::Class {
+            name,
+            superclass,
+            methods,
+        })
+    }
+
+    fn methods(&mut self) -> Result<Vec<Stmt>> {
+        let mut methods = Vec::new();
+        while !self.check(token_type::TokenType::RIGHT_BRACE) && !self.is_at_end() {
+            methods.push(self.function("method")?);
+        }
+        Ok(methods)
+    }
+
+    fn function(&mut self, kind: &str) -> Result<Stmt> {
+        let name = self.consume(
+            token_type::TokenType::IDENTIFIER,
+            &format!("Expect {} name.", kind),
+        )?;
+        self.consume(
+            token_type::TokenType::LEFT_PAREN,
+            &format!("Expect '(' after {} name.", kind),
+        )?;
+        let function_type = match kind {
+            "method" if name.lexeme == "init" => FunctionType::Initializer,
+            "method" => FunctionType::Method,
+            _ => {
+                self.declare(&name, false)?;
+                FunctionType::Function
+            }
+        };
+        let (params, body) = self.function_body(kind, function_type)?;
+        Ok(Stmt::Function { name, params, body })
+    }
+
+    /// Parses the parameter list and body of a function, after its opening '('.
+    fn function_body(
+        &mut self,
+        kind: &str,
+        function_type: FunctionType,
+    ) -> Result<(Vec<Token>, Vec<Stmt>)> {
+        let mut params = Vec::new();
+        if !self.check(token_type::TokenType::RIGHT_PAREN) {
+            loop {
+                params.push(
+                    self.consume(token_type::TokenType::IDENTIFIER, "Expect parameter name.")?,
+                );
+                if !self.match_token(vec![token_type::TokenType::COMMA]) {
+                    break;
+                }
+            }
+        }
+        self.consume(
+            token_type::TokenType::RIGHT_PAREN,
+            "Expect ')' after parameters.",
+        )?;
+        self.consume(
+            token_type::TokenType::LEFT_BRACE,
+            &format!("Expect '{{' before {} body.", kind),
+        )?;
+
+        let enclosing_function = std::mem::replace(&mut self.current_function, function_type);
+        let enclosing_loops = std::mem::take(&mut self.loops);
+        let body = self.block(&params);
+        self.current_function = enclosing_function;
+        self.loops = enclosing_loops;
+        Ok((params, body?))
+    }
+
     fn var_declaration(&mut self) -> Result<Stmt> {
         let name = self.consume(token_type::TokenType::IDENTIFIER, "Expect variable name.")?;
         let mut initializer = None;
@@ -66,12 +296,42 @@ impl Parser {
             token_type::TokenType::SEMICOLON,
             "Expect ';' after variable declaration.",
         )?;
+        self.declare(&name, false)?;
         Ok(Stmt::Var { name, initializer })
     }
 
+    fn const_declaration(&mut self) -> Result<Stmt> {
+        let name = self.consume(token_type::TokenType::IDENTIFIER, "Expect constant name.")?;
+        self.consume(
+            token_type::TokenType::EQUAL,
+            "Expect '=' after constant name.",
+        )?;
+        let initializer = self.expression()?;
+        self.consume(
+            token_type::TokenType::SEMICOLON,
+            "Expect ';' after constant declaration.",
+        )?;
+        self.declare(&name, true)?;
+        Ok(Stmt::Const { name, initializer })
+    }
+
     fn statement(&mut self) -> Result<Stmt> {
+        if self.check(token_type::TokenType::IDENTIFIER)
+            && self.check_next(token_type::TokenType::COLON)
+        {
+            return self.labeled_statement();
+        }
+        if self.match_token(vec![token_type::TokenType::FOR]) {
+            return self.for_statement(None);
+        }
         if self.match_token(vec![token_type::TokenType::WHILE]) {
-            return self.while_statement();
+            return self.while_statement(None);
+        }
+        if self.match_token(vec![token_type::TokenType::BREAK]) {
+            return self.break_statement();
+        }
+        if self.match_token(vec![token_type::TokenType::CONTINUE]) {
+            return self.continue_statement();
         }
         if self.match_token(vec![token_type::TokenType::IF]) {
             return self.if_statement();
@@ -79,13 +339,120 @@ impl Parser {
         if self.match_token(vec![token_type::TokenType::PRINT]) {
             return self.print_statement();
         }
+        if self.match_token(vec![token_type::TokenType::RETURN]) {
+            return self.return_statement();
+        }
+        if self.match_token(vec![token_type::TokenType::THROW]) {
+            return self.throw_statement();
+        }
+        if self.match_token(vec![token_type::TokenType::IMPORT]) {
+            return self.import_statement();
+        }
+        if self.match_token(vec![token_type::TokenType::TRY]) {
+            return self.try_statement();
+        }
         if self.match_token(vec![token_type::TokenType::LEFT_BRACE]) {
             return self.block_statement();
         }
+        if self.check(token_type::TokenType::MATCH) {
+            return self.match_statement();
+        }
         self.expression_statement()
     }
 
-    fn while_statement(&mut self) -> Result<Stmt> {
+    fn labeled_statement(&mut self) -> Result<Stmt> {
+        let label = self.advance().clone();
+        self.advance();
+        if self.match_token(vec![token_type::TokenType::FOR]) {
+            return self.for_statement(Some(label));
+        }
+        if self.match_token(vec![token_type::TokenType::WHILE]) {
+            return self.while_statement(Some(label));
+        }
+        Err(ParserError {
+            message: "Expect loop after label.".to_string(),
+            token: self.peek().clone(),
+        })
+    }
+
+    /// Parses a loop body with `label` pushed onto the stack of enclosing loops.
+    fn loop_body(
+        &mut self,
+        label: &Option<Token>,
+        parse_body: fn(&mut Self) -> Result<Stmt>,
+    ) -> Result<Stmt> {
+        if let Some(label) = label {
+            if self.loops.contains(&Some(label.lexeme.clone())) {
+                return Err(ParserError {
+                    message: format!("Label '{}' is already in use.", label.lexeme),
+                    token: label.clone(),
+                });
+            }
+        }
+        self.loops.push(label.as_ref().map(|l| l.lexeme.clone()));
+        let body = parse_body(self);
+        self.loops.pop();
+        body
+    }
+
+    /// Desugars `for (init; cond; incr) body` into
+    /// `{ init; while (cond) body }` with `incr` as the loop's increment.
+    /// The loop variable is scoped to the loop, as in the desugared block.
+    fn for_statement(&mut self, label: Option<Token>) -> Result<Stmt> {
+        self.scopes.push(HashMap::new());
+        let statement = self.for_clauses_and_body(label);
+        self.scopes.pop();
+        statement
+    }
+
+    fn for_clauses_and_body(&mut self, label: Option<Token>) -> Result<Stmt> {
+        self.consume(token_type::TokenType::LEFT_PAREN, "Expect '(' after 'for'.")?;
+
+        let initializer = if self.match_token(vec![token_type::TokenType::SEMICOLON]) {
+            None
+        } else if self.match_token(vec![token_type::TokenType::VAR]) {
+            Some(self.var_declaration()?)
+        } else {
+            Some(self.expression_statement()?)
+        };
+
+        let condition = if self.check(token_type::TokenType::SEMICOLON) {
+            Expr::Literal {
+                value: token::Literal::Boolean(true),
+            }
+        } else {
+            self.expression()?
+        };
+        self.consume(
+            token_type::TokenType::SEMICOLON,
+            "Expect ';' after loop condition.",
+        )?;
+
+        let increment = if self.check(token_type::TokenType::RIGHT_PAREN) {
+            None
+        } else {
+            Some(self.expression()?)
+        };
+        self.consume(
+            token_type::TokenType::RIGHT_PAREN,
+            "Expect ')' after for clauses.",
+        )?;
+
+        let mut body = Stmt::While {
+            condition,
+            body: Box::new(self.loop_body(&label, Self::statement)?),
+            increment,
+            label,
+        };
+        if let Some(initializer) = initializer {
+            body = Stmt::Block {
+                statements: vec![initializer, body],
+            };
+        }
+        Ok(body)
+    }
+
+    fn while_statement(&mut self, label: Option<Token>) -> Result<Stmt> {
         self.consume(
             token_type::TokenType::LEFT_PAREN,
             "Expect '(' after 'while'.",
@@ -97,9 +464,56 @@ impl Parser {
             "Expect ')' after while condition.",
         )?;
 
-        let body = Box::new(self.declaration()?);
+        let body = Box::new(self.loop_body(&label, Self::declaration)?);
+
+        Ok(Stmt::While {
+            condition,
+            body,
+            increment: None,
+            label,
+        })
+    }
+
+    fn break_statement(&mut self) -> Result<Stmt> {
+        let keyword = self.previous().clone();
+        let label = self.jump_label(&keyword)?;
+        self.consume(
+            token_type::TokenType::SEMICOLON,
+            "Expect ';' after 'break'.",
+        )?;
+        Ok(Stmt::Break { keyword, label })
+    }
+
+    fn continue_statement(&mut self) -> Result<Stmt> {
+        let keyword = self.previous().clone();
+        let label = self.jump_label(&keyword)?;
+        self.consume(
+            token_type::TokenType::SEMICOLON,
+            "Expect ';' after 'continue'.",
+        )?;
+        Ok(Stmt::Continue { keyword, label })
+    }
 
-        Ok(Stmt::While { condition, body })
+    /// Parses the optional label after `break`/`continue` and checks that the
+    /// jump has a loop to target.
+    fn jump_label(&mut self, keyword: &Token) -> Result<Option<Token>> {
+        if self.loops.is_empty() {
+            return Err(ParserError {
+                message: format!("Can't use '{}' outside of a loop.", keyword.lexeme),
+                token: keyword.clone(),
+            });
+        }
+        if !self.match_token(vec![token_type::TokenType::IDENTIFIER]) {
+            return Ok(None);
+        }
+        let label = self.previous().clone();
+        if !self.loops.contains(&Some(label.lexeme.clone())) {
+            return Err(ParserError {
+                message: format!("No enclosing loop labeled '{}'.", label.lexeme),
+                token: label,
+            });
+        }
+        Ok(Some(label))
     }
 
     fn if_statement(&mut self) -> Result<Stmt> {
@@ -123,9 +537,35 @@ impl Parser {
     }
 
     fn print_statement(&mut self) -> Result<Stmt> {
+        let keyword = self.previous().clone();
         let expr = self.expression()?;
         self.consume(token_type::TokenType::SEMICOLON, "Expect ';' after value.")?;
-        Ok(Stmt::Print(expr))
+        Ok(Stmt::Print { keyword, expr })
+    }
+
+    fn return_statement(&mut self) -> Result<Stmt> {
+        let keyword = self.previous().clone();
+        if self.current_function == FunctionType::None {
+            return Err(ParserError {
+                message: "Can't return from top-level code.".to_string(),
+                token: keyword,
+            });
+        }
+        let value = if self.check(token_type::TokenType::SEMICOLON) {
+            None
+        } else if self.current_function == FunctionType::Initializer {
+            return Err(ParserError {
+                message: "Can't return a value from an initializer.".to_string(),
+                token: keyword,
+            });
+        } else {
+            Some(self.expression()?)
+        };
+        self.consume(
+            token_type::TokenType::SEMICOLON,
+            "Expect ';' after return value.",
+        )?;
+        Ok(Stmt::Return { keyword, value })
     }
 
     fn expression_statement(&mut self) -> Result<Stmt> {
@@ -134,7 +574,149 @@ impl Parser {
         Ok(Stmt::Expr(expr))
     }
 
+    /// Like an expression statement, except that a bare `match` needs no ';'
+    /// after its closing '}'.
+    fn match_statement(&mut self) -> Result<Stmt> {
+        let expr = self.expression()?;
+        if matches!(expr, Expr::Match { .. }) {
+            self.match_token(vec![token_type::TokenType::SEMICOLON]);
+        } else {
+            self.consume(token_type::TokenType::SEMICOLON, "Expect ';' after value.")?;
+        }
+        Ok(Stmt::Expr(expr))
+    }
+
+    fn throw_statement(&mut self) -> Result<Stmt> {
+        let keyword = self.previous().clone();
+        let value = self.expression()?;
+        self.consume(
+            token_type::TokenType::SEMICOLON,
+            "Expect ';' after thrown value.",
+        )?;
+        Ok(Stmt::Throw { keyword, value })
+    }
+
+    fn import_statement(&mut self) -> Result<Stmt> {
+        let keyword = self.previous().clone();
+        let mut alias = None;
+        let mut names = Vec::new();
+        if self.match_token(vec![token_type::TokenType::LEFT_BRACE]) {
+            loop {
+                let name =
+                    self.consume(token_type::TokenType::IDENTIFIER, "Expect name to import.")?;
+                let local = if self.match_token(vec![token_type::TokenType::AS]) {
+                    self.consume(
+                        token_type::TokenType::IDENTIFIER,
+                        "Expect local name after 'as'.",
+                    )?
+                } else {
+                    name.clone()
+                };
+                names.push((name, local));
+                if !self.match_token(vec![token_type::TokenType::COMMA]) {
+                    break;
+                }
+            }
+            self.consume(
+                token_type::TokenType::RIGHT_BRACE,
+                "Expect '}' after imported names.",
+            )?;
+            self.consume(
+                token_type::TokenType::FROM,
+                "Expect 'from' after imported names.",
+            )?;
+        }
+        let path = match self.consume(token_type::TokenType::STRING, "Expect module path.")? {
+            Token {
+                literal: Some(token::Literal::String(path)),
+                ..
+            } => path,
+            _ => unreachable!("string tokens carry their value"),
+        };
+        if names.is_empty() && self.match_token(vec![token_type::TokenType::AS]) {
+            alias = Some(self.consume(
+                token_type::TokenType::IDENTIFIER,
+                "Expect module name after 'as'.",
+            )?);
+        }
+        self.consume(token_type::TokenType::SEMICOLON, "Expect ';' after import.")?;
+        for binding in alias.iter().chain(names.iter().map(|(_, local)| local)) {
+            self.declare(binding, false)?;
+        }
+        Ok(Stmt::Import {
+            keyword,
+            path,
+            alias,
+            names,
+        })
+    }
+
+    fn try_statement(&mut self) -> Result<Stmt> {
+        self.consume(token_type::TokenType::LEFT_BRACE, "Expect '{' after 'try'.")?;
+        let body = self.block(&[])?;
+        let catch = if self.match_token(vec![token_type::TokenType::CATCH]) {
+            self.consume(
+                token_type::TokenType::LEFT_PAREN,
+                "Expect '(' after 'catch'.",
+            )?;
+            let name = self.consume(
+                token_type::TokenType::IDENTIFIER,
+                "Expect exception variable name.",
+            )?;
+            self.consume(
+                token_type::TokenType::RIGHT_PAREN,
+                "Expect ')' after exception variable.",
+            )?;
+            self.consume(
+                token_type::TokenType::LEFT_BRACE,
+                "Expect '{' before catch body.",
+            )?;
+            let handler = self.block(std::slice::from_ref(&name))?;
+            Some((name, handler))
+        } else {
+            None
+        };
+        let finally = if self.match_token(vec![token_type::TokenType::FINALLY]) {
+            self.consume(
+                token_type::TokenType::LEFT_BRACE,
+                "Expect '{' after 'finally'.",
+            )?;
+            Some(self.block(&[])?)
+        } else {
+            None
+        };
+        if catch.is_none() && finally.is_none() {
+            return Err(ParserError {
+                message: "Expect 'catch' or 'finally' after try block.".to_string(),
+                token: self.peek().clone(),
+            });
+        }
+        Ok(Stmt::Try {
+            body,
+            catch,
+            finally,
+        })
+    }
+
     fn block_statement(&mut self) -> Result<Stmt> {
+        Ok(Stmt::Block {
+            statements: self.block(&[])?,
+        })
+    }
+
+    /// Parses the declarations of a block, after its opening '{', in a new
+    /// scope where `bindings` (such as parameters) are already declared.
+    fn block(&mut self, bindings: &[Token]) -> Result<Vec<Stmt>> {
+        self.scopes.push(HashMap::new());
+        let statements = self.block_declarations(bindings);
+        self.scopes.pop();
+        statements
+    }
+
+    fn block_declarations(&mut self, bindings: &[Token]) -> Result<Vec<Stmt>> {
+        for binding in bindings {
+            self.declare(binding, false)?;
+        }
         let mut statements = Vec::new();
         while !self.check(token_type::TokenType::RIGHT_BRACE) && !self.is_at_end() {
             statements.push(self.declaration()?);
@@ -143,7 +725,7 @@ impl Parser {
             token_type::TokenType::RIGHT_BRACE,
             "Expect '}' after block.",
         )?;
-        Ok(Stmt::Block { statements })
+        Ok(statements)
     }
 
     fn synchronize(&mut self) {
@@ -156,11 +738,17 @@ impl Parser {
                 token_type::TokenType::CLASS
                 | token_type::TokenType::FUN
                 | token_type::TokenType::VAR
+                | token_type::TokenType::CONST
+                | token_type::TokenType::LET
                 | token_type::TokenType::FOR
                 | token_type::TokenType::IF
                 | token_type::TokenType::WHILE
                 | token_type::TokenType::PRINT
-                | token_type::TokenType::RETURN => return,
+                | token_type::TokenType::RETURN
+                | token_type::TokenType::THROW
+                | token_type::TokenType::TRY
+                | token_type::TokenType::IMPORT
+                | token_type::TokenType::EXPORT => return,
                 _ => {
                     self.advance();
                 }
@@ -189,6 +777,13 @@ impl Parser {
         self.peek().token_type == t
     }
 
+    fn check_next(&mut self, t: token_type::TokenType) -> bool {
+        match self.tokens.get(self.current + 1) {
+            Some(token) => token.token_type == t,
+            None => false,
+        }
+    }
+
     fn advance(&mut self) -> &Token {
         if !self.is_at_end() {
             self.current += 1;
@@ -209,25 +804,125 @@ impl Parser {
     }
 
     fn assignment(&mut self) -> Result<Expr> {
-        let expr = self.or()?;
+        let expr = self.conditional()?;
         if self.match_token(vec![token_type::TokenType::EQUAL]) {
             let equals = self.previous().clone();
             let value = self.assignment()?;
             match expr {
-                Expr::Variable { name } => Ok(Expr::Assignment {
+                Expr::Variable { name } => {
+                    self.check_assignable(&name)?;
+                    Ok(Expr::Assignment {
+                        name,
+                        value: Box::new(value),
+                    })
+                }
+                Expr::Get { object, name } => Ok(Expr::Set {
+                    object,
                     name,
                     value: Box::new(value),
                 }),
+                Expr::Index {
+                    object,
+                    bracket,
+                    index,
+                } => Ok(Expr::IndexSet {
+                    object,
+                    bracket,
+                    index,
+                    value: Box::new(value),
+                }),
                 _ => Err(ParserError {
                     message: "Invalid assignment target.".to_string(),
                     token: equals,
                 }),
             }
+        } else if self.match_token(vec![
+            token_type::TokenType::PLUS_EQUAL,
+            token_type::TokenType::MINUS_EQUAL,
+            token_type::TokenType::STAR_EQUAL,
+            token_type::TokenType::SLASH_EQUAL,
+            token_type::TokenType::PERCENT_EQUAL,
+        ]) {
+            let token = self.previous().clone();
+            let value = self.assignment()?;
+            if !Self::is_assignable(&expr) {
+                return Err(ParserError {
+                    message: "Invalid assignment target.".to_string(),
+                    token,
+                });
+            }
+            if let Expr::Variable { name } = &expr {
+                self.check_assignable(name)?;
+            }
+            Ok(Expr::CompoundAssignment {
+                target: Box::new(expr),
+                operator: token.clone().into(),
+                token,
+                value: Box::new(value),
+            })
         } else {
             Ok(expr)
         }
     }
 
+    fn is_assignable(expr: &Expr) -> bool {
+        matches!(
+            expr,
+            Expr::Variable { .. } | Expr::Get { .. } | Expr::Index { .. }
+        )
+    }
+
+    fn increment(&self, token: Token, target: Expr, prefix: bool) -> Result<Expr> {
+        if !Self::is_assignable(&target) {
+            return Err(ParserError {
+                message: format!("Invalid '{}' target.", token.lexeme),
+                token,
+            });
+        }
+        if let Expr::Variable { name } = &target {
+            self.check_assignable(name)?;
+        }
+        Ok(Expr::Increment {
+            target: Box::new(target),
+            operator: token.clone().into(),
+            token,
+            prefix,
+        })
+    }
+
+    /// `?:` is right-associative: `a ? b : c ? d : e` is `a ? b : (c ? d : e)`.
+    fn conditional(&mut self) -> Result<Expr> {
+        let condition = self.coalesce()?;
+        if self.match_token(vec![token_type::TokenType::QUESTION]) {
+            let then_branch = self.expression()?;
+            self.consume(
+                token_type::TokenType::COLON,
+                "Expect ':' after then branch of conditional expression.",
+            )?;
+            let else_branch = self.conditional()?;
+            return Ok(Expr::Conditional {
+                condition: Box::new(condition),
+                then_branch: Box::new(then_branch),
+                else_branch: Box::new(else_branch),
+            });
+        }
+        Ok(condition)
+    }
+
+    fn coalesce(&mut self) -> Result<Expr> {
+        let mut expr = self.or()?;
+        while self.match_token(vec![token_type::TokenType::QUESTION_QUESTION]) {
+            let operator = self.previous().clone();
+            let right = self.or()?;
+            expr = Expr::Logical {
+                left: Box::new(expr),
+                operator: operator.into(),
+                right: Box::new(right),
+            };
+        }
+        Ok(expr)
+    }
+
     fn or(&mut self) -> Result<Expr> {
         let mut expr = self.and()?;
         while self.match_token(vec![token_type::TokenType::OR]) {
@@ -243,10 +938,10 @@ impl Parser {
     }
 
     fn and(&mut self) -> Result<Expr> {
-        let mut expr = self.equality()?;
+        let mut expr = self.bit_or()?;
         while self.match_token(vec![token_type::TokenType::AND]) {
             let operator = self.previous().clone();
-            let right = self.equality()?;
+            let right = self.bit_or()?;
             expr = Expr::Logical {
                 left: Box::new(expr),
                 operator: operator.into(),
@@ -256,36 +951,103 @@ impl Parser {
         Ok(expr)
     }
 
-    fn equality(&mut self) -> Result<Expr> {
-        let mut expr = self.comparison()?;
-        while self.match_token(vec![
-            token_type::TokenType::BANG_EQUAL,
-            token_type::TokenType::EQUAL_EQUAL,
-        ]) {
+    // The bitwise operators follow C's precedence: `|` < `^` < `&` < equality.
+    fn bit_or(&mut self) -> Result<Expr> {
+        let mut expr = self.bit_xor()?;
+        while self.match_token(vec![token_type::TokenType::PIPE]) {
             let operator = self.previous().clone();
-            let right = self.comparison()?;
+            let right = self.bit_xor()?;
             expr = Expr::Binary {
                 left: Box::new(expr),
-                operator: operator.into(),
+                operator: operator.clone().into(),
+                token: operator,
                 right: Box::new(right),
             };
         }
         Ok(expr)
     }
 
-    fn comparison(&mut self) -> Result<Expr> {
-        let mut expr = self.term()?;
+    fn bit_xor(&mut self) -> Result<Expr> {
+        let mut expr = self.bit_and()?;
+        while self.match_token(vec![token_type::TokenType::CARET]) {
+            let operator = self.previous().clone();
+            let right = self.bit_and()?;
+            expr = Expr::Binary {
+                left: Box::new(expr),
+                operator: operator.clone().into(),
+                token: operator,
+                right: Box::new(right),
+            };
+        }
+        Ok(expr)
+    }
+
+    fn bit_and(&mut self) -> Result<Expr> {
+        let mut expr = self.equality()?;
+        while self.match_token(vec![token_type::TokenType::AMPERSAND]) {
+            let operator = self.previous().clone();
+            let right = self.equality()?;
+            expr = Expr::Binary {
+                left: Box::new(expr),
+                operator: operator.clone().into(),
+                token: operator,
+                right: Box::new(right),
+            };
+        }
+        Ok(expr)
+    }
+
+    fn equality(&mut self) -> Result<Expr> {
+        let mut expr = self.comparison()?;
+        while self.match_token(vec![
+            token_type::TokenType::BANG_EQUAL,
+            token_type::TokenType::EQUAL_EQUAL,
+        ]) {
+            let operator = self.previous().clone();
+            let right = self.comparison()?;
+            expr = Expr::Binary {
+                left: Box::new(expr),
+                operator: operator.clone().into(),
+                token: operator,
+                right: Box::new(right),
+            };
+        }
+        Ok(expr)
+    }
+
+    fn comparison(&mut self) -> Result<Expr> {
+        let mut expr = self.shift()?;
         while self.match_token(vec![
             token_type::TokenType::GREATER,
             token_type::TokenType::GREATER_EQUAL,
             token_type::TokenType::LESS,
             token_type::TokenType::LESS_EQUAL,
+            token_type::TokenType::IN,
+        ]) {
+            let operator = self.previous().clone();
+            let right = self.shift()?;
+            expr = Expr::Binary {
+                left: Box::new(expr),
+                operator: operator.clone().into(),
+                token: operator,
+                right: Box::new(right),
+            };
+        }
+        Ok(expr)
+    }
+
+    fn shift(&mut self) -> Result<Expr> {
+        let mut expr = self.term()?;
+        while self.match_token(vec![
+            token_type::TokenType::LESS_LESS,
+            token_type::TokenType::GREATER_GREATER,
         ]) {
             let operator = self.previous().clone();
             let right = self.term()?;
             expr = Expr::Binary {
                 left: Box::new(expr),
-                operator: operator.into(),
+                operator: operator.clone().into(),
+                token: operator,
                 right: Box::new(right),
             };
         }
@@ -302,7 +1064,8 @@ impl Parser {
             let right = self.factor()?;
             expr = Expr::Binary {
                 left: Box::new(expr),
-                operator: operator.into(),
+                operator: operator.clone().into(),
+                token: operator,
                 right: Box::new(right),
             };
         }
@@ -314,12 +1077,15 @@ impl Parser {
         while self.match_token(vec![
             token_type::TokenType::SLASH,
             token_type::TokenType::STAR,
+            token_type::TokenType::PERCENT,
+            token_type::TokenType::TILDE_SLASH,
         ]) {
             let operator = self.previous().clone();
             let right = self.unary()?;
             expr = Expr::Binary {
                 left: Box::new(expr),
-                operator: operator.into(),
+                operator: operator.clone().into(),
+                token: operator,
                 right: Box::new(right),
             };
         }
@@ -327,19 +1093,289 @@ impl Parser {
     }
 
     fn unary(&mut self) -> Result<Expr> {
+        if self.match_token(vec![
+            token_type::TokenType::PLUS_PLUS,
+            token_type::TokenType::MINUS_MINUS,
+        ]) {
+            let token = self.previous().clone();
+            let target = self.unary()?;
+            return self.increment(token, target, true);
+        }
         if self.match_token(vec![
             token_type::TokenType::BANG,
             token_type::TokenType::MINUS,
+            token_type::TokenType::TILDE,
         ]) {
             let operator = self.previous().clone();
             let right = self.unary()?;
             Ok(Expr::Unary {
-                operator: operator.into(),
+                operator: operator.clone().into(),
+                token: operator,
                 right: Box::new(right),
             })
         } else {
-            self.primary()
+            self.power()
+        }
+    }
+
+    /// `**` binds tighter than a unary operator on its left, so `-2 ** 2` is
+    /// `-(2 ** 2)`, but its right operand may itself be unary, as in `2 ** -1`.
+    /// Parsing that operand with `unary` also makes `**` right-associative.
+    fn power(&mut self) -> Result<Expr> {
+        let expr = self.postfix()?;
+        if self.match_token(vec![token_type::TokenType::STAR_STAR]) {
+            let operator = self.previous().clone();
+            let right = self.unary()?;
+            return Ok(Expr::Binary {
+                left: Box::new(expr),
+                operator: operator.clone().into(),
+                token: operator,
+                right: Box::new(right),
+            });
+        }
+        Ok(expr)
+    }
+
+    fn postfix(&mut self) -> Result<Expr> {
+        let expr = self.call()?;
+        if self.match_token(vec![
+            token_type::TokenType::PLUS_PLUS,
+            token_type::TokenType::MINUS_MINUS,
+        ]) {
+            let token = self.previous().clone();
+            return self.increment(token, expr, false);
+        }
+        Ok(expr)
+    }
+
+    fn call(&mut self) -> Result<Expr> {
+        let mut expr = self.primary()?;
+        loop {
+            if self.match_token(vec![token_type::TokenType::LEFT_PAREN]) {
+                expr = self.finish_call(expr)?;
+            } else if self.match_token(vec![token_type::TokenType::DOT]) {
+                let name = self.consume(
+                    token_type::TokenType::IDENTIFIER,
+                    "Expect property name after '.'.",
+                )?;
+                expr = Expr::Get {
+                    object: Box::new(expr),
+                    name,
+                };
+            } else if self.match_token(vec![token_type::TokenType::LEFT_BRACKET]) {
+                let index = self.expression()?;
+                let bracket = self.consume(
+                    token_type::TokenType::RIGHT_BRACKET,
+                    "Expect ']' after index.",
+                )?;
+                expr = Expr::Index {
+                    object: Box::new(expr),
+                    bracket,
+                    index: Box::new(index),
+                };
+            } else {
+                break;
+            }
+        }
+        Ok(expr)
+    }
+
+    fn finish_call(&mut self, callee: Expr) -> Result<Expr> {
+        let mut arguments = Vec::new();
+        if !self.check(token_type::TokenType::RIGHT_PAREN) {
+            loop {
+                arguments.push(self.expression()?);
+                if !self.match_token(vec![token_type::TokenType::COMMA]) {
+                    break;
+                }
+            }
+        }
+        let paren = self.consume(
+            token_type::TokenType::RIGHT_PAREN,
+            "Expect ')' after arguments.",
+        )?;
+        Ok(Expr::Call {
+            callee: Box::new(callee),
+            paren,
+            arguments,
+        })
+    }
+
+    /// Parses the rest of an interpolated string once its first INTERPOLATION
+    /// token has been consumed. The scanner emits one INTERPOLATION per `${`,
+    /// each followed by the embedded expression, and ends with a STRING.
+    fn interpolation(&mut self) -> Result<Expr> {
+        let start = self.previous().clone();
+        let mut parts = Vec::new();
+        loop {
+            parts.push(Expr::Literal {
+                value: self.previous().literal.clone().unwrap(),
+            });
+            parts.push(self.expression()?);
+            if self.match_token(vec![token_type::TokenType::INTERPOLATION]) {
+                continue;
+            }
+            self.consume(
+                token_type::TokenType::STRING,
+                "Expect '}' after interpolated expression.",
+            )?;
+            parts.push(Expr::Literal {
+                value: self.previous().literal.clone().unwrap(),
+            });
+            return Ok(Expr::Interpolation { start, parts });
+        }
+    }
+
+    /// Parses a `match` expression, after the keyword.
+    fn match_expression(&mut self) -> Result<Expr> {
+        let keyword = self.previous().clone();
+        self.consume(
+            token_type::TokenType::LEFT_PAREN,
+            "Expect '(' after 'match'.",
+        )?;
+        let subject = self.expression()?;
+        self.consume(
+            token_type::TokenType::RIGHT_PAREN,
+            "Expect ')' after match value.",
+        )?;
+        self.consume(
+            token_type::TokenType::LEFT_BRACE,
+            "Expect '{' before match arms.",
+        )?;
+        let mut arms = Vec::new();
+        while !self.check(token_type::TokenType::RIGHT_BRACE) && !self.is_at_end() {
+            let arm = self.match_arm()?;
+            let is_block = matches!(arm.body, ArmBody::Block(_));
+            arms.push(arm);
+            // The comma after a block body is optional.
+            if !self.match_token(vec![token_type::TokenType::COMMA]) && !is_block {
+                break;
+            }
+        }
+        self.consume(
+            token_type::TokenType::RIGHT_BRACE,
+            "Expect '}' after match arms.",
+        )?;
+        Ok(Expr::Match {
+            keyword,
+            subject: Box::new(subject),
+            arms,
+        })
+    }
+
+    /// Parses one arm. Its guard and body get a scope of their own, holding
+    /// the names its pattern binds.
+    fn match_arm(&mut self) -> Result<MatchArm> {
+        let pattern = self.pattern()?;
+        let bindings: Vec<Token> = pattern.bindings().into_iter().cloned().collect();
+        for (i, binding) in bindings.iter().enumerate() {
+            if bindings[..i].iter().any(|b| b.lexeme == binding.lexeme) {
+                return Err(ParserError {
+                    message: format!("Duplicate binding '{}' in pattern.", binding.lexeme),
+                    token: binding.clone(),
+                });
+            }
+        }
+        self.scopes.push(HashMap::new());
+        let arm = self.match_arm_rest(pattern, &bindings);
+        self.scopes.pop();
+        arm
+    }
+
+    fn match_arm_rest(&mut self, pattern: Pattern, bindings: &[Token]) -> Result<MatchArm> {
+        for binding in bindings {
+            self.declare(binding, false)?;
+        }
+        let guard = if self.match_token(vec![token_type::TokenType::IF]) {
+            Some(self.expression()?)
+        } else {
+            None
+        };
+        self.consume(
+            token_type::TokenType::FAT_ARROW,
+            "Expect '=>' after match pattern.",
+        )?;
+        let body = if self.match_token(vec![token_type::TokenType::LEFT_BRACE]) {
+            ArmBody::Block(self.block_declarations(&[])?)
+        } else {
+            ArmBody::Expr(self.expression()?)
+        };
+        Ok(MatchArm {
+            pattern,
+            guard,
+            body,
+        })
+    }
+
+    fn pattern(&mut self) -> Result<Pattern> {
+        let mut alternatives = vec![self.single_pattern()?];
+        while self.match_token(vec![token_type::TokenType::PIPE]) {
+            alternatives.push(self.single_pattern()?);
+        }
+        if alternatives.len() == 1 {
+            return Ok(alternatives.remove(0));
+        }
+        if let Some(binding) = alternatives.iter().flat_map(Pattern::bindings).next() {
+            return Err(ParserError {
+                message: "Can't bind names in alternative patterns.".to_string(),
+                token: binding.clone(),
+            });
+        }
+        Ok(Pattern::Alternation(alternatives))
+    }
+
+    fn single_pattern(&mut self) -> Result<Pattern> {
+        if self.match_token(vec![token_type::TokenType::FALSE]) {
+            return Ok(Pattern::Literal(token::Literal::Boolean(false)));
+        }
+        if self.match_token(vec![token_type::TokenType::TRUE]) {
+            return Ok(Pattern::Literal(token::Literal::Boolean(true)));
+        }
+        if self.match_token(vec![token_type::TokenType::NIL]) {
+            return Ok(Pattern::Literal(token::Literal::Nil));
+        }
+        if self.match_token(vec![
+            token_type::TokenType::NUMBER,
+            token_type::TokenType::STRING,
+        ]) {
+            return Ok(Pattern::Literal(self.previous().literal.clone().unwrap()));
+        }
+        if self.check(token_type::TokenType::MINUS)
+            && self.check_next(token_type::TokenType::NUMBER)
+        {
+            self.advance();
+            let number = match self.advance().literal.clone().unwrap() {
+                token::Literal::Int(i) => token::Literal::Int(-i),
+                token::Literal::Float(f) => token::Literal::Float(-f),
+                literal => literal,
+            };
+            return Ok(Pattern::Literal(number));
+        }
+        if self.match_token(vec![token_type::TokenType::IDENTIFIER]) {
+            let name = self.previous().clone();
+            if name.lexeme == "_" {
+                return Ok(Pattern::Wildcard);
+            }
+            return Ok(Pattern::Binding(name));
         }
+        if self.match_token(vec![token_type::TokenType::LEFT_BRACKET]) {
+            let mut elements = Vec::new();
+            while !self.check(token_type::TokenType::RIGHT_BRACKET) {
+                elements.push(self.pattern()?);
+                if !self.match_token(vec![token_type::TokenType::COMMA]) {
+                    break;
+                }
+            }
+            self.consume(
+                token_type::TokenType::RIGHT_BRACKET,
+                "Expect ']' after list pattern.",
+            )?;
+            return Ok(Pattern::List(elements));
+        }
+        Err(ParserError {
+            token: self.peek().clone(),
+            message: "Expect pattern.".to_string(),
+        })
     }
 
     fn primary(&mut self) -> Result<Expr> {
@@ -359,6 +1395,52 @@ impl Parser {
             });
         }
 
+        if self.match_token(vec![token_type::TokenType::SUPER]) {
+            let keyword = self.previous().clone();
+            self.consume(token_type::TokenType::DOT, "Expect '.' after 'super'.")?;
+            let method = self.consume(
+                token_type::TokenType::IDENTIFIER,
+                "Expect superclass method name.",
+            )?;
+            return match self.current_class {
+                ClassType::None => Err(ParserError {
+                    message: "Can't use 'super' outside of a class.".to_string(),
+                    token: keyword,
+                }),
+                ClassType::Class => Err(ParserError {
+                    message: "Can't use 'super' in a class with no superclass.".to_string(),
+                    token: keyword,
+                }),
+                ClassType::Subclass => Ok(Expr::Super { keyword, method }),
+            };
+        }
+
+        if self.match_token(vec![token_type::TokenType::FUN]) {
+            let keyword = self.previous().clone();
+            self.consume(token_type::TokenType::LEFT_PAREN, "Expect '(' after 'fun'.")?;
+            let (params, body) = self.function_body("function", FunctionType::Function)?;
+            return Ok(Expr::Lambda {
+                keyword,
+                params,
+                body,
+            });
+        }
+
+        if self.match_token(vec![token_type::TokenType::MATCH]) {
+            return self.match_expression();
+        }
+
+        if self.match_token(vec![token_type::TokenType::THIS]) {
+            let keyword = self.previous().clone();
+            if self.current_class == ClassType::None {
+                return Err(ParserError {
+                    message: "Can't use 'this' outside of a class.".to_string(),
+                    token: keyword,
+                });
+            }
+            return Ok(Expr::This { keyword });
+        }
+
         if self.match_token(vec![token_type::TokenType::IDENTIFIER]) {
             return Ok(Expr::Variable {
                 name: self.previous().clone(),
@@ -374,6 +1456,45 @@ impl Parser {
             });
         }
 
+        if self.match_token(vec![token_type::TokenType::INTERPOLATION]) {
+            return self.interpolation();
+        }
+
+        if self.match_token(vec![token_type::TokenType::LEFT_BRACKET]) {
+            let mut elements = Vec::new();
+            while !self.check(token_type::TokenType::RIGHT_BRACKET) {
+                elements.push(self.expression()?);
+                if !self.match_token(vec![token_type::TokenType::COMMA]) {
+                    break;
+                }
+            }
+            self.consume(
+                token_type::TokenType::RIGHT_BRACKET,
+                "Expect ']' after list elements.",
+            )?;
+            return Ok(Expr::List { elements });
+        }
+
+        // A '{' in expression position can't start a block, so it is a map.
+        if self.match_token(vec![token_type::TokenType::LEFT_BRACE]) {
+            let brace = self.previous().clone();
+            let mut entries = Vec::new();
+            while !self.check(token_type::TokenType::RIGHT_BRACE) {
+                let key = self.expression()?;
+                self.consume(token_type::TokenType::COLON, "Expect ':' after map key.")?;
+                let value = self.expression()?;
+                entries.push((key, value));
+                if !self.match_token(vec![token_type::TokenType::COMMA]) {
+                    break;
+                }
+            }
+            self.consume(
+                token_type::TokenType::RIGHT_BRACE,
+                "Expect '}' after map entries.",
+            )?;
+            return Ok(Expr::Map { brace, entries });
+        }
+
         if self.match_token(vec![token_type::TokenType::LEFT_PAREN]) {
             let expr = self.expression()?;
             self.consume(
@@ -406,10 +1527,23 @@ impl Parser {
 mod tests {
     use tests::token::Literal;
 
+    use crate::lexer::scanner::Scanner;
     use crate::parser::expr::Operator;
 
     use super::*;
 
+    fn parse(source: &str) -> Vec<Stmt> {
+        let tokens = Scanner::new(source).scan_tokens().clone();
+        Parser::new(tokens).parse()
+    }
+
+    fn parse_has_error(source: &str) -> bool {
+        let tokens = Scanner::new(source).scan_tokens().clone();
+        let mut parser = Parser::new(tokens);
+        parser.parse();
+        parser.had_error()
+    }
+
     #[test]
     fn test_parser() {
         let tokens = vec![
@@ -426,6 +1560,7 @@ mod tests {
                 Some(Literal::Int(2)),
                 1,
             ),
+            Token::new(token_type::TokenType::SEMICOLON, ";".to_string(), None, 1),
             Token::new(token_type::TokenType::EOF, "".to_string(), None, 2),
         ];
         let expr = Expr::Binary {
@@ -433,6 +1568,7 @@ mod tests {
                 value: token::Literal::Int(1),
             }),
             operator: Operator::Plus,
+            token: Token::new(token_type::TokenType::PLUS, "+".to_string(), None, 1),
             right: Box::new(Expr::Literal {
                 value: token::Literal::Int(2),
             }),
@@ -440,4 +1576,331 @@ mod tests {
 
         assert_eq!(Parser::new(tokens).parse(), [Stmt::Expr(expr)]);
     }
+
+    #[test]
+    fn test_function_declaration() {
+        let stmts = parse("fun add(a, b) { return a + b; } add(1, 2);");
+        assert_eq!(stmts.len(), 2);
+        match &stmts[0] {
+            Stmt::Function { name, params, body } => {
+                assert_eq!(name.lexeme, "add");
+                assert_eq!(
+                    params.iter().map(|p| p.lexeme.as_str()).collect::<Vec<_>>(),
+                    ["a", "b"]
+                );
+                assert!(matches!(body[0], Stmt::Return { value: Some(_), .. }));
+            }
+            stmt => panic!("expected function declaration, got {}", stmt),
+        }
+        match &stmts[1] {
+            Stmt::Expr(Expr::Call { arguments, .. }) => assert_eq!(arguments.len(), 2),
+            stmt => panic!("expected call, got {}", stmt),
+        }
+    }
+
+    #[test]
+    fn test_return_outside_function() {
+        assert_eq!(parse("return 1;"), []);
+    }
+
+    #[test]
+    fn test_class_declaration() {
+        let stmts = parse("class A { init(x) { this.x = x; } get() { return this.x; } }");
+        match &stmts[..] {
+            [Stmt::Class {
+                name,
+                superclass,
+                methods,
+            }] => {
+                assert_eq!(name.lexeme, "A");
+                assert_eq!(*superclass, None);
+                assert_eq!(methods.len(), 2);
+            }
+            stmts => panic!("expected a class declaration, got {:?}", stmts),
+        }
+    }
+
+    #[test]
+    fn test_this_outside_class() {
+        assert_eq!(parse("print this;"), []);
+    }
+
+    #[test]
+    fn test_return_value_from_initializer() {
+        assert!(parse_has_error("class A { init() { return 1; } }"));
+        assert!(!parse_has_error("class A { init() { return; } }"));
+    }
+
+    #[test]
+    fn test_super_outside_subclass() {
+        assert_eq!(parse("print super.x;"), []);
+        assert_eq!(parse("class A { m() { super.m(); } }").len(), 0);
+        assert_eq!(
+            parse("class A {} class B < A { m() { super.m(); } }").len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_for_desugars_to_while() {
+        let stmts = parse("for (var i = 0; i < 3; i = i + 1) print i;");
+        match &stmts[..] {
+            [Stmt::Block { statements }] => {
+                assert!(matches!(statements[0], Stmt::Var { .. }));
+                match &statements[1] {
+                    Stmt::While {
+                        body, increment, ..
+                    } => {
+                        assert!(matches!(**body, Stmt::Print { .. }));
+                        assert!(increment.is_some());
+                    }
+                    stmt => panic!("expected while loop, got {}", stmt),
+                }
+            }
+            stmts => panic!("expected a block, got {:?}", stmts),
+        }
+    }
+
+    #[test]
+    fn test_for_clauses_are_optional() {
+        let stmts = parse("for (;;) print 1;");
+        match &stmts[..] {
+            [Stmt::While {
+                condition, body, ..
+            }] => {
+                assert_eq!(
+                    *condition,
+                    Expr::Literal {
+                        value: Literal::Boolean(true)
+                    }
+                );
+                assert!(matches!(**body, Stmt::Print { .. }));
+            }
+            stmts => panic!("expected a bare while loop, got {:?}", stmts),
+        }
+    }
+
+    #[test]
+    fn test_labeled_break_and_continue() {
+        let stmts = parse("outer: while (true) { for (;;) { continue outer; break; } }");
+        match &stmts[..] {
+            [Stmt::While { label, .. }] => {
+                assert_eq!(label.as_ref().map(|l| l.lexeme.as_str()), Some("outer"))
+            }
+            stmts => panic!("expected a labeled while loop, got {:?}", stmts),
+        }
+    }
+
+    #[test]
+    fn test_jump_outside_loop() {
+        assert_eq!(parse("break;"), []);
+        assert_eq!(parse("continue;"), []);
+        assert_eq!(parse("while (true) { fun f() { break; } }").len(), 0);
+        assert_eq!(parse("a: while (true) { break b; }").len(), 0);
+    }
+
+    #[test]
+    fn test_anonymous_function_expression() {
+        let stmts = parse("fun (a) { return a; }(1); var f = fun () {};");
+        assert_eq!(stmts.len(), 2);
+        match &stmts[0] {
+            Stmt::Expr(Expr::Call { callee, .. }) => {
+                assert!(matches!(**callee, Expr::Lambda { ref params, .. } if params.len() == 1))
+            }
+            stmt => panic!("expected a call to a lambda, got {}", stmt),
+        }
+    }
+
+    #[test]
+    fn test_list_literal_and_index_assignment() {
+        let stmts = parse("[1, 2,][0] = 3;");
+        match &stmts[..] {
+            [Stmt::Expr(Expr::IndexSet { object, .. })] => {
+                assert!(matches!(**object, Expr::List { ref elements } if elements.len() == 2))
+            }
+            stmts => panic!("expected an index assignment, got {:?}", stmts),
+        }
+    }
+
+    #[test]
+    fn test_map_literal_versus_block() {
+        let stmts = parse("{ print 1; } var m = {\"a\": 1, 2: 3,};");
+        assert!(matches!(stmts[0], Stmt::Block { .. }));
+        match &stmts[1] {
+            Stmt::Var {
+                initializer: Some(Expr::Map { entries, .. }),
+                ..
+            } => assert_eq!(entries.len(), 2),
+            stmt => panic!("expected a map literal, got {}", stmt),
+        }
+    }
+
+    #[test]
+    fn test_string_interpolation() {
+        let stmts = parse("print \"total: ${a + b}!\";");
+        match &stmts[..] {
+            [Stmt::Print {
+                expr: expr @ Expr::Interpolation { parts, .. },
+                ..
+            }] => {
+                assert_eq!(parts.len(), 3);
+                assert_eq!(expr.to_string(), "\"total: ${(a + b)}!\"");
+            }
+            stmts => panic!("expected an interpolated string, got {:?}", stmts),
+        }
+    }
+
+    #[test]
+    fn test_power_precedence() {
+        let stmts = parse("-2 ** 2; 2 ** 3 ** 2; 2 ** -1 * 3; 7 ~/ 2 % 3;");
+        let printed: Vec<String> = stmts.iter().map(|s| s.to_string()).collect();
+        assert_eq!(
+            printed,
+            vec![
+                "(-(2 ** 2))",
+                "(2 ** (3 ** 2))",
+                "((2 ** (-1)) * 3)",
+                "((7 ~/ 2) % 3)"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compound_assignment_and_increments() {
+        let stmts = parse("a.b[0] *= 2 + 1; x = ++y - z--; a - -b;");
+        let printed: Vec<String> = stmts.iter().map(|s| s.to_string()).collect();
+        assert_eq!(
+            printed,
+            vec!["(a.b[0] *= (2 + 1))", "(x = ((++y) - (z--)))", "(a - (-b))"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_compound_assignment_targets() {
+        for source in ["(a + b) += 1;", "++(a + b);", "1--;"] {
+            assert!(parse_has_error(source), "{} should not parse", source);
+        }
+    }
+
+    #[test]
+    fn test_conditional_and_coalesce_precedence() {
+        let stmts = parse("x = a or b ? c : d ? e : f; a ?? b or c ? 1 : 2;");
+        let printed: Vec<String> = stmts.iter().map(|s| s.to_string()).collect();
+        assert_eq!(
+            printed,
+            vec![
+                "(x = ((a or b) ? c : (d ? e : f)))",
+                "((a ?? (b or c)) ? 1 : 2)"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bitwise_precedence() {
+        let stmts = parse("a | b ^ c & d == e; 1 << 2 + 3 < 4 >> 1; a and b | c; ~-a;");
+        let printed: Vec<String> = stmts.iter().map(|s| s.to_string()).collect();
+        assert_eq!(
+            printed,
+            vec![
+                "(a | (b ^ (c & (d == e))))",
+                "((1 << (2 + 3)) < (4 >> 1))",
+                "(a and (b | c))",
+                "(~(-a))"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_try_statement() {
+        let stmts = parse("try { throw 1; } catch (e) { print e; } finally { print 2; }");
+        match &stmts[..] {
+            [Stmt::Try {
+                body,
+                catch: Some((name, handler)),
+                finally: Some(finally),
+            }] => {
+                assert!(matches!(body[..], [Stmt::Throw { .. }]));
+                assert_eq!(name.lexeme, "e");
+                assert_eq!((handler.len(), finally.len()), (1, 1));
+            }
+            stmts => panic!("expected a try statement, got {:?}", stmts),
+        }
+        assert!(parse_has_error("try { }"));
+    }
+
+    #[test]
+    fn test_import_and_export() {
+        let stmts = parse(
+            "import \"lib.amm\" as lib; import { a, b as c } from \"x.amm\"; export fun f() {}",
+        );
+        let printed: Vec<String> = stmts.iter().map(|s| s.to_string()).collect();
+        assert_eq!(
+            printed,
+            vec![
+                "import \"lib.amm\" as lib;",
+                "import { a, b as c } from \"x.amm\";",
+                "export fun f()"
+            ]
+        );
+        for source in ["{ export var v = 1; }", "export print 1;"] {
+            assert!(parse_has_error(source), "{} should not parse", source);
+        }
+    }
+
+    #[test]
+    fn test_const_assignments_rejected_statically() {
+        for source in [
+            "const a = 1; a = 2;",
+            "const a = 1; a += 2;",
+            "const a = 1; fun f() { a++; }",
+            "const a = 1; { --a; }",
+            "const a = 1; var a = 2;",
+            "const a;",
+            "let a = 1; a = 2;",
+            "let a = 1; const a = 2;",
+        ] {
+            assert!(parse_has_error(source), "{} should not parse", source);
+        }
+        for source in [
+            "const a = 1; { var a = 2; a = 3; }",
+            "const a = 1; fun f(a) { a = 2; }",
+            "const i = 1; for (var i = 0; i < 2; i++) {}",
+            "const a = 1; try {} catch (a) { a = 2; }",
+            "fun f() { b = 1; } const b = 2;",
+            "let a = 1; { let a = 2; }",
+            "export let a = 1;",
+        ] {
+            assert!(!parse_has_error(source), "{} should parse", source);
+        }
+    }
+
+    #[test]
+    fn test_match_expression() {
+        let stmts = parse(
+            "var r = match (x) { 1 | -2 => \"small\", [a, _] if a > 0 => a, _ => { print x; } };",
+        );
+        match &stmts[0] {
+            Stmt::Var {
+                initializer: Some(expr),
+                ..
+            } => assert_eq!(
+                expr.to_string(),
+                "match (x) { 1 | -2 => small, [a, _] if (a > 0) => a, _ => { print x } }"
+            ),
+            stmt => panic!("expected a var declaration, got {}", stmt),
+        }
+        // A bare match statement needs no trailing ';'.
+        assert_eq!(parse("match (x) { _ => 1 } print 2;").len(), 2);
+
+        for source in [
+            "match (x) { [a, a] => 1 };",
+            "match (x) { a | 1 => 1 };",
+            "match (x) { 1 2 };",
+            "match (x) { 1 => 1 2 => 2 };",
+            "match (x) { x + 1 => 1 };",
+            "const c = 1; match (x) { y => c = y };",
+        ] {
+            assert!(parse_has_error(source), "{} should not parse", source);
+        }
+    }
 }
@@ -0,0 +1,125 @@
+use core::fmt;
+
+use crate::lexer::token::{self, Token};
+
+use super::{expr::Expr, interpreter::Object, stmt::Stmt};
+
+/// The left-hand side of a `match` arm.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    /// `_`, which matches any value without binding it.
+    Wildcard,
+    Literal(token::Literal),
+    /// A name, which matches any value and binds it.
+    Binding(Token),
+    /// `a | b`, which matches when any of its alternatives does.
+    Alternation(Vec<Pattern>),
+    /// `[a, b]`, which matches a list of exactly that length whose elements
+    /// match in turn.
+    List(Vec<Pattern>),
+}
+
+impl Pattern {
+    /// Tests `value` against this pattern, pushing the names it binds onto
+    /// `bindings`. On failure `bindings` may hold a partial set.
+    pub fn matches(&self, value: &Object, bindings: &mut Vec<(Token, Object)>) -> bool {
+        match self {
+            Pattern::Wildcard => true,
+            Pattern::Literal(literal) => Object::from(literal) == *value,
+            Pattern::Binding(name) => {
+                bindings.push((name.clone(), value.clone()));
+                true
+            }
+            Pattern::Alternation(alternatives) => alternatives
+                .iter()
+                .any(|alternative| alternative.matches(value, bindings)),
+            Pattern::List(elements) => match value {
+                Object::List(list) => {
+                    let list = list.borrow();
+                    list.len() == elements.len()
+                        && elements
+                            .iter()
+                            .zip(list.iter())
+                            .all(|(pattern, element)| pattern.matches(element, bindings))
+                }
+                _ => false,
+            },
+        }
+    }
+
+    /// The names this pattern binds, in source order.
+    pub fn bindings(&self) -> Vec<&Token> {
+        match self {
+            Pattern::Wildcard | Pattern::Literal(_) => Vec::new(),
+            Pattern::Binding(name) => vec![name],
+            Pattern::Alternation(patterns) | Pattern::List(patterns) => {
+                patterns.iter().flat_map(Pattern::bindings).collect()
+            }
+        }
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pattern::Wildcard => write!(f, "_"),
+            Pattern::Literal(token::Literal::String(s)) => write!(f, "{:?}", s),
+            Pattern::Literal(literal) => write!(f, "{}", literal),
+            Pattern::Binding(name) => write!(f, "{}", name.lexeme),
+            Pattern::Alternation(alternatives) => write!(
+                f,
+                "{}",
+                alternatives
+                    .iter()
+                    .map(|a| a.to_string())
+                    .collect::<Vec<String>>()
+                    .join(" | ")
+            ),
+            Pattern::List(elements) => write!(
+                f,
+                "[{}]",
+                elements
+                    .iter()
+                    .map(|e| e.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArmBody {
+    Expr(Expr),
+    /// A block body runs for its effects; the arm's value is nil.
+    Block(Vec<Stmt>),
+}
+
+/// `pattern if guard => body`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<Expr>,
+    pub body: ArmBody,
+}
+
+impl fmt::Display for MatchArm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.pattern)?;
+        if let Some(guard) = &self.guard {
+            write!(f, " if {}", guard)?;
+        }
+        match &self.body {
+            ArmBody::Expr(expr) => write!(f, " => {}", expr),
+            ArmBody::Block(statements) => write!(
+                f,
+                " => {{ {} }}",
+                statements
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            ),
+        }
+    }
+}
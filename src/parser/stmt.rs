@@ -6,8 +6,9 @@ use super::expr::Expr;
 
 pub trait Visitor<T> {
     fn visit_expr(&mut self, expr: &Expr) -> T;
-    fn visit_print(&mut self, expr: &Expr) -> T;
+    fn visit_print(&mut self, keyword: &Token, expr: &Expr) -> T;
     fn visit_var(&mut self, name: &Token, initializer: &Option<Expr>) -> T;
+    fn visit_const(&mut self, name: &Token, initializer: &Expr) -> T;
     fn visit_block(&mut self, statements: &Vec<Stmt>) -> T;
     fn visit_if(
         &mut self,
@@ -15,7 +16,33 @@ pub trait Visitor<T> {
         then_branch: &Stmt,
         else_branch: &Option<Box<Stmt>>,
     ) -> T;
-    fn visit_while(&mut self, condition: &Expr, body: &Stmt) -> T;
+    fn visit_while(
+        &mut self,
+        condition: &Expr,
+        body: &Stmt,
+        increment: &Option<Expr>,
+        label: &Option<Token>,
+    ) -> T;
+    fn visit_function(&mut self, name: &Token, params: &[Token], body: &[Stmt]) -> T;
+    fn visit_return(&mut self, keyword: &Token, value: &Option<Expr>) -> T;
+    fn visit_break(&mut self, keyword: &Token, label: &Option<Token>) -> T;
+    fn visit_continue(&mut self, keyword: &Token, label: &Option<Token>) -> T;
+    fn visit_class(&mut self, name: &Token, superclass: &Option<Expr>, methods: &[Stmt]) -> T;
+    fn visit_throw(&mut self, keyword: &Token, value: &Expr) -> T;
+    fn visit_import(
+        &mut self,
+        keyword: &Token,
+        path: &str,
+        alias: &Option<Token>,
+        names: &[(Token, Token)],
+    ) -> T;
+    fn visit_export(&mut self, declaration: &Stmt) -> T;
+    fn visit_try(
+        &mut self,
+        body: &[Stmt],
+        catch: &Option<(Token, Vec<Stmt>)>,
+        finally: &Option<Vec<Stmt>>,
+    ) -> T;
 }
 
 pub trait Acceptor<T> {
@@ -25,11 +52,18 @@ pub trait Acceptor<T> {
 #[derive(Debug, Clone, PartialEq)]
 pub enum Stmt {
     Expr(Expr),
-    Print(Expr),
+    Print {
+        keyword: Token,
+        expr: Expr,
+    },
     Var {
         name: Token,
         initializer: Option<Expr>,
     },
+    Const {
+        name: Token,
+        initializer: Expr,
+    },
     Block {
         statements: Vec<Stmt>,
     },
@@ -38,9 +72,56 @@ pub enum Stmt {
         then_branch: Box<Stmt>,
         else_branch: Option<Box<Stmt>>,
     },
+    /// `increment` runs after every iteration, including ones cut short by
+    /// `continue`; it is how `for` loops are desugared.
     While {
         condition: Expr,
         body: Box<Stmt>,
+        increment: Option<Expr>,
+        label: Option<Token>,
+    },
+    Break {
+        keyword: Token,
+        label: Option<Token>,
+    },
+    Continue {
+        keyword: Token,
+        label: Option<Token>,
+    },
+    Function {
+        name: Token,
+        params: Vec<Token>,
+        body: Vec<Stmt>,
+    },
+    Return {
+        keyword: Token,
+        value: Option<Expr>,
+    },
+    Class {
+        name: Token,
+        superclass: Option<Expr>,
+        methods: Vec<Stmt>,
+    },
+    Throw {
+        keyword: Token,
+        value: Expr,
+    },
+    /// `import "path" as alias;` binds the whole module, while
+    /// `import { name as local } from "path";` binds each pair in `names`.
+    Import {
+        keyword: Token,
+        path: String,
+        alias: Option<Token>,
+        names: Vec<(Token, Token)>,
+    },
+    Export {
+        declaration: Box<Stmt>,
+    },
+    /// `catch` holds the name the caught value is bound to and the handler.
+    Try {
+        body: Vec<Stmt>,
+        catch: Option<(Token, Vec<Stmt>)>,
+        finally: Option<Vec<Stmt>>,
     },
 }
 
@@ -48,15 +129,43 @@ impl<T> Acceptor<T> for Stmt {
     fn accept(&self, visitor: &mut dyn Visitor<T>) -> T {
         match self {
             Stmt::Expr(expr) => visitor.visit_expr(expr),
-            Stmt::Print(expr) => visitor.visit_print(expr),
+            Stmt::Print { keyword, expr } => visitor.visit_print(keyword, expr),
             Stmt::Var { name, initializer } => visitor.visit_var(&name, initializer),
+            Stmt::Const { name, initializer } => visitor.visit_const(name, initializer),
             Stmt::Block { statements } => visitor.visit_block(statements),
             Stmt::If {
                 condition,
                 then_branch,
                 else_branch,
             } => visitor.visit_if(condition, then_branch, else_branch),
-            Stmt::While { condition, body } => visitor.visit_while(condition, body),
+            Stmt::While {
+                condition,
+                body,
+                increment,
+                label,
+            } => visitor.visit_while(condition, body, increment, label),
+            Stmt::Break { keyword, label } => visitor.visit_break(keyword, label),
+            Stmt::Continue { keyword, label } => visitor.visit_continue(keyword, label),
+            Stmt::Function { name, params, body } => visitor.visit_function(name, params, body),
+            Stmt::Return { keyword, value } => visitor.visit_return(keyword, value),
+            Stmt::Class {
+                name,
+                superclass,
+                methods,
+            } => visitor.visit_class(name, superclass, methods),
+            Stmt::Throw { keyword, value } => visitor.visit_throw(keyword, value),
+            Stmt::Import {
+                keyword,
+                path,
+                alias,
+                names,
+            } => visitor.visit_import(keyword, path, alias, names),
+            Stmt::Export { declaration } => visitor.visit_export(declaration),
+            Stmt::Try {
+                body,
+                catch,
+                finally,
+            } => visitor.visit_try(body, catch, finally),
         }
     }
 }
@@ -65,8 +174,11 @@ impl fmt::Display for Stmt {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Stmt::Expr(expr) => write!(f, "{}", expr),
-            Stmt::Print(expr) => write!(f, "print {}", expr),
+            Stmt::Print { expr, .. } => write!(f, "print {}", expr),
             Stmt::Var { name, .. } => write!(f, "var {};", name),
+            Stmt::Const { name, initializer } => {
+                write!(f, "const {} = {};", name.lexeme, initializer)
+            }
             Stmt::Block { statements } => {
                 write!(
                     f,
@@ -92,7 +204,81 @@ impl fmt::Display for Stmt {
                     .map_or("".to_string(), |b| b.to_string())
             ),
 
-            Stmt::While { condition, body } => write!(f, "while ({}) {} ", condition, body),
+            Stmt::While {
+                condition,
+                body,
+                increment,
+                label,
+            } => {
+                if let Some(label) = label {
+                    write!(f, "{}: ", label.lexeme)?;
+                }
+                match increment {
+                    Some(increment) => {
+                        write!(f, "while ({}; {}) {} ", condition, increment, body)
+                    }
+                    None => write!(f, "while ({}) {} ", condition, body),
+                }
+            }
+            Stmt::Break { label, .. } => match label {
+                Some(label) => write!(f, "break {};", label.lexeme),
+                None => write!(f, "break;"),
+            },
+            Stmt::Continue { label, .. } => match label {
+                Some(label) => write!(f, "continue {};", label.lexeme),
+                None => write!(f, "continue;"),
+            },
+            Stmt::Function { name, params, .. } => write!(
+                f,
+                "fun {}({})",
+                name.lexeme,
+                params
+                    .iter()
+                    .map(|p| p.lexeme.clone())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Stmt::Return { value, .. } => match value {
+                Some(value) => write!(f, "return {};", value),
+                None => write!(f, "return;"),
+            },
+            Stmt::Class {
+                name, superclass, ..
+            } => match superclass {
+                Some(superclass) => write!(f, "class {} < {}", name.lexeme, superclass),
+                None => write!(f, "class {}", name.lexeme),
+            },
+            Stmt::Throw { value, .. } => write!(f, "throw {};", value),
+            Stmt::Import {
+                path, alias, names, ..
+            } => match alias {
+                Some(alias) => write!(f, "import \"{}\" as {};", path, alias.lexeme),
+                None if names.is_empty() => write!(f, "import \"{}\";", path),
+                None => write!(
+                    f,
+                    "import {{ {} }} from \"{}\";",
+                    names
+                        .iter()
+                        .map(|(name, local)| match name.lexeme == local.lexeme {
+                            true => name.lexeme.clone(),
+                            false => format!("{} as {}", name.lexeme, local.lexeme),
+                        })
+                        .collect::<Vec<String>>()
+                        .join(", "),
+                    path
+                ),
+            },
+            Stmt::Export { declaration } => write!(f, "export {}", declaration),
+            Stmt::Try { catch, finally, .. } => {
+                write!(f, "try")?;
+                if let Some((name, _)) = catch {
+                    write!(f, " catch ({})", name.lexeme)?;
+                }
+                if finally.is_some() {
+                    write!(f, " finally")?;
+                }
+                Ok(())
+            }
         }
     }
 }